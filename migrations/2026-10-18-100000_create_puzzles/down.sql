@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE puzzle_openings;
+DROP TABLE puzzle_themes;
+DROP TABLE puzzles
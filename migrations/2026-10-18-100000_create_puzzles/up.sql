@@ -0,0 +1,32 @@
+CREATE TABLE puzzles (
+    puzzle_id TEXT NOT NULL PRIMARY KEY,
+    fen TEXT NOT NULL,
+    moves TEXT NOT NULL,
+    rating INTEGER NOT NULL,
+    rd INTEGER NOT NULL,
+    popularity INTEGER NOT NULL,
+    nb_plays INTEGER NOT NULL,
+    themes TEXT NOT NULL,
+    game_url TEXT NOT NULL,
+    opening_tags TEXT NOT NULL
+);
+
+CREATE INDEX puzzles_rating_idx ON puzzles (rating);
+
+-- The themes and opening tags are space separated in the puzzles table,
+-- they're kept here one per row too so the searches can use an index.
+CREATE TABLE puzzle_themes (
+    puzzle_id TEXT NOT NULL,
+    theme TEXT NOT NULL,
+    PRIMARY KEY (puzzle_id, theme)
+);
+
+CREATE INDEX puzzle_themes_theme_idx ON puzzle_themes (theme, puzzle_id);
+
+CREATE TABLE puzzle_openings (
+    puzzle_id TEXT NOT NULL,
+    opening_tag TEXT NOT NULL,
+    PRIMARY KEY (puzzle_id, opening_tag)
+);
+
+CREATE INDEX puzzle_openings_opening_tag_idx ON puzzle_openings (opening_tag, puzzle_id);
@@ -0,0 +1,11 @@
+CREATE TABLE attempts (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    puzzle_id TEXT NOT NULL,
+    attempted_at BIGINT NOT NULL,
+    solved BOOLEAN NOT NULL,
+    wrong_moves INTEGER NOT NULL,
+    hints INTEGER NOT NULL,
+    time_taken_ms BIGINT NOT NULL
+);
+
+CREATE INDEX attempts_puzzle_id_idx ON attempts (puzzle_id);
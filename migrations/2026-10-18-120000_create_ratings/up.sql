@@ -0,0 +1,8 @@
+CREATE TABLE ratings (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    rated_at BIGINT NOT NULL,
+    puzzle_id TEXT NOT NULL,
+    rating DOUBLE NOT NULL,
+    deviation DOUBLE NOT NULL,
+    volatility DOUBLE NOT NULL
+);
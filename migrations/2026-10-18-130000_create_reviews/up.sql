@@ -0,0 +1,18 @@
+CREATE TABLE reviews (
+    puzzle_id TEXT NOT NULL PRIMARY KEY,
+    fen TEXT NOT NULL,
+    moves TEXT NOT NULL,
+    rating INTEGER NOT NULL,
+    rd INTEGER NOT NULL,
+    popularity INTEGER NOT NULL,
+    nb_plays INTEGER NOT NULL,
+    themes TEXT NOT NULL,
+    game_url TEXT NOT NULL,
+    opening_tags TEXT NOT NULL,
+    ease DOUBLE NOT NULL,
+    interval_days INTEGER NOT NULL,
+    repetitions INTEGER NOT NULL,
+    due_at BIGINT NOT NULL
+);
+
+CREATE INDEX reviews_due_at_idx ON reviews (due_at);
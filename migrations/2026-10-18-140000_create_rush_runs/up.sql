@@ -0,0 +1,6 @@
+CREATE TABLE rush_runs (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    finished_at BIGINT NOT NULL,
+    minutes INTEGER NOT NULL,
+    score INTEGER NOT NULL
+);
@@ -0,0 +1,5 @@
+CREATE TABLE streaks (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    finished_at BIGINT NOT NULL,
+    length INTEGER NOT NULL
+);
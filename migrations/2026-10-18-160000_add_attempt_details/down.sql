@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE attempts DROP COLUMN puzzle_rating;
+ALTER TABLE attempts DROP COLUMN themes;
+ALTER TABLE attempts DROP COLUMN opening_tags;
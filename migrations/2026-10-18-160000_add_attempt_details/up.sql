@@ -0,0 +1,5 @@
+-- What the statistics are broken down by, so they don't depend
+-- on the puzzle still being in the csv or the database
+ALTER TABLE attempts ADD COLUMN puzzle_rating INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE attempts ADD COLUMN themes TEXT NOT NULL DEFAULT '';
+ALTER TABLE attempts ADD COLUMN opening_tags TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,51 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE saved_searches;
+
+ALTER TABLE streaks DROP COLUMN profile_id;
+ALTER TABLE rush_runs DROP COLUMN profile_id;
+DROP INDEX ratings_profile_id_idx;
+ALTER TABLE ratings DROP COLUMN profile_id;
+DROP INDEX attempts_profile_id_idx;
+ALTER TABLE attempts DROP COLUMN profile_id;
+
+-- Only the default profile's favorites and reviews are kept
+CREATE TABLE reviews_global (
+    puzzle_id TEXT NOT NULL PRIMARY KEY,
+    fen TEXT NOT NULL,
+    moves TEXT NOT NULL,
+    rating INTEGER NOT NULL,
+    rd INTEGER NOT NULL,
+    popularity INTEGER NOT NULL,
+    nb_plays INTEGER NOT NULL,
+    themes TEXT NOT NULL,
+    game_url TEXT NOT NULL,
+    opening_tags TEXT NOT NULL,
+    ease DOUBLE NOT NULL,
+    interval_days INTEGER NOT NULL,
+    repetitions INTEGER NOT NULL,
+    due_at BIGINT NOT NULL
+);
+INSERT INTO reviews_global
+    SELECT puzzle_id, fen, moves, rating, rd, popularity, nb_plays, themes, game_url, opening_tags, ease, interval_days, repetitions, due_at FROM reviews WHERE profile_id = 1;
+DROP TABLE reviews;
+ALTER TABLE reviews_global RENAME TO reviews;
+CREATE INDEX reviews_due_at_idx ON reviews (due_at);
+
+CREATE TABLE favs_global (
+    puzzle_id TEXT NOT NULL PRIMARY KEY,
+    fen TEXT NOT NULL,
+    moves TEXT NOT NULL,
+    rating INTEGER NOT NULL,
+    rd INTEGER NOT NULL,
+    popularity INTEGER NOT NULL,
+    nb_plays INTEGER NOT NULL,
+    themes TEXT NOT NULL,
+    game_url TEXT NOT NULL,
+    opening_tags TEXT NOT NULL
+);
+INSERT INTO favs_global
+    SELECT puzzle_id, fen, moves, rating, rd, popularity, nb_plays, themes, game_url, opening_tags FROM favs WHERE profile_id = 1;
+DROP TABLE favs;
+ALTER TABLE favs_global RENAME TO favs;
+
+DROP TABLE profiles;
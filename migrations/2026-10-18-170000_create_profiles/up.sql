@@ -0,0 +1,67 @@
+CREATE TABLE profiles (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE
+);
+
+-- Everything saved before the profiles belongs to the default one
+INSERT INTO profiles (id, name) VALUES (1, 'Default');
+
+-- The same puzzle can be a favorite or be in the review queue of several
+-- profiles, so these two get the profile in their primary key.
+CREATE TABLE favs_by_profile (
+    profile_id INTEGER NOT NULL DEFAULT 1,
+    puzzle_id TEXT NOT NULL,
+    fen TEXT NOT NULL,
+    moves TEXT NOT NULL,
+    rating INTEGER NOT NULL,
+    rd INTEGER NOT NULL,
+    popularity INTEGER NOT NULL,
+    nb_plays INTEGER NOT NULL,
+    themes TEXT NOT NULL,
+    game_url TEXT NOT NULL,
+    opening_tags TEXT NOT NULL,
+    PRIMARY KEY (profile_id, puzzle_id)
+);
+INSERT INTO favs_by_profile (puzzle_id, fen, moves, rating, rd, popularity, nb_plays, themes, game_url, opening_tags)
+    SELECT puzzle_id, fen, moves, rating, rd, popularity, nb_plays, themes, game_url, opening_tags FROM favs;
+DROP TABLE favs;
+ALTER TABLE favs_by_profile RENAME TO favs;
+
+CREATE TABLE reviews_by_profile (
+    profile_id INTEGER NOT NULL DEFAULT 1,
+    puzzle_id TEXT NOT NULL,
+    fen TEXT NOT NULL,
+    moves TEXT NOT NULL,
+    rating INTEGER NOT NULL,
+    rd INTEGER NOT NULL,
+    popularity INTEGER NOT NULL,
+    nb_plays INTEGER NOT NULL,
+    themes TEXT NOT NULL,
+    game_url TEXT NOT NULL,
+    opening_tags TEXT NOT NULL,
+    ease DOUBLE NOT NULL,
+    interval_days INTEGER NOT NULL,
+    repetitions INTEGER NOT NULL,
+    due_at BIGINT NOT NULL,
+    PRIMARY KEY (profile_id, puzzle_id)
+);
+INSERT INTO reviews_by_profile (puzzle_id, fen, moves, rating, rd, popularity, nb_plays, themes, game_url, opening_tags, ease, interval_days, repetitions, due_at)
+    SELECT puzzle_id, fen, moves, rating, rd, popularity, nb_plays, themes, game_url, opening_tags, ease, interval_days, repetitions, due_at FROM reviews;
+DROP TABLE reviews;
+ALTER TABLE reviews_by_profile RENAME TO reviews;
+CREATE INDEX reviews_due_at_idx ON reviews (profile_id, due_at);
+
+ALTER TABLE attempts ADD COLUMN profile_id INTEGER NOT NULL DEFAULT 1;
+CREATE INDEX attempts_profile_id_idx ON attempts (profile_id);
+ALTER TABLE ratings ADD COLUMN profile_id INTEGER NOT NULL DEFAULT 1;
+CREATE INDEX ratings_profile_id_idx ON ratings (profile_id);
+ALTER TABLE rush_runs ADD COLUMN profile_id INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE streaks ADD COLUMN profile_id INTEGER NOT NULL DEFAULT 1;
+
+CREATE TABLE saved_searches (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    profile_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    preset TEXT NOT NULL,
+    UNIQUE (profile_id, name)
+);
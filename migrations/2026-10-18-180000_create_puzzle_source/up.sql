@@ -0,0 +1,6 @@
+-- The csv the puzzles table was last filled from, to tell when it was replaced
+CREATE TABLE puzzle_source (
+    id INTEGER NOT NULL PRIMARY KEY,
+    csv_path TEXT NOT NULL,
+    modified_at BIGINT NOT NULL
+);
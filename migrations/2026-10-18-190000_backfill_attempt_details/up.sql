@@ -0,0 +1,15 @@
+-- The attempts saved before their details were recorded get them
+-- from the imported puzzles, or from the favorites.
+UPDATE attempts SET
+    puzzle_rating = COALESCE(
+        (SELECT rating FROM puzzles WHERE puzzles.puzzle_id = attempts.puzzle_id),
+        (SELECT rating FROM favs WHERE favs.puzzle_id = attempts.puzzle_id LIMIT 1)),
+    themes = COALESCE(
+        (SELECT themes FROM puzzles WHERE puzzles.puzzle_id = attempts.puzzle_id),
+        (SELECT themes FROM favs WHERE favs.puzzle_id = attempts.puzzle_id LIMIT 1)),
+    opening_tags = COALESCE(
+        (SELECT opening_tags FROM puzzles WHERE puzzles.puzzle_id = attempts.puzzle_id),
+        (SELECT opening_tags FROM favs WHERE favs.puzzle_id = attempts.puzzle_id LIMIT 1))
+WHERE puzzle_rating = 0 AND (
+    EXISTS (SELECT 1 FROM puzzles WHERE puzzles.puzzle_id = attempts.puzzle_id) OR
+    EXISTS (SELECT 1 FROM favs WHERE favs.puzzle_id = attempts.puzzle_id));
@@ -1,6 +1,7 @@
-use crate::{styles, search_tab::TaticsThemes, search_tab::OpeningSide, lang, openings::{Openings, Variation}};
+use crate::{styles, search_tab::TaticsThemes, search_tab::ThemeFilter, search_tab::StatsFilter, search_tab::OpeningSide, search_tab::PuzzleFilter, search_tab::SearchPreset, search_tab::PuzzleOrder, lang, db, openings::{Openings, Variation}};
 use once_cell::sync::Lazy;
-use chess::{Board, ChessMove, Piece, Square};
+use chess::{Board, ChessMove, Color, Piece, Square};
+use crate::puzzles::PuzzleTab;
 use std::str::FromStr;
 use iced::Font;
 
@@ -17,6 +18,43 @@ pub const CHESS_ALPHA: Font = iced::Font::with_name("Chess Alpha");
 pub enum GameMode {
     Puzzle,
     Analysis,
+    Rush,
+    Streak,
+}
+
+impl GameMode {
+    // A puzzle rush or a streak, where the puzzles are played one after the other
+    pub fn is_run(self) -> bool {
+        matches!(self, GameMode::Rush | GameMode::Streak)
+    }
+}
+
+// How the pieces are drawn while solving, for visualization training
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoardDisplay {
+    #[default]
+    Normal,
+    // No pieces at all, the position is only given as text
+    Blindfold,
+    // The pieces are shown at the start of the puzzle and hidden after a few seconds
+    Fading,
+    // Every piece drawn in the same colour
+    Monochrome,
+}
+
+impl BoardDisplay {
+    pub const ALL: [BoardDisplay; 4] = [
+        BoardDisplay::Normal, BoardDisplay::Blindfold, BoardDisplay::Fading, BoardDisplay::Monochrome
+    ];
+
+    pub fn get_tr_key(&self) -> &str {
+        match self {
+            BoardDisplay::Normal => "display_normal",
+            BoardDisplay::Blindfold => "display_blindfold",
+            BoardDisplay::Fading => "display_fading",
+            BoardDisplay::Monochrome => "display_monochrome",
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -37,10 +75,35 @@ pub struct OfflinePuzzlesConfig {
     pub export_pgs: i32,
     pub last_min_rating: i32,
     pub last_max_rating: i32,
+    #[serde(default = "default_min_moves")]
+    pub last_min_moves: i32,
+    #[serde(default = "default_max_moves")]
+    pub last_max_moves: i32,
     pub last_theme: TaticsThemes,
+    #[serde(default)]
+    pub last_theme_filter: ThemeFilter,
+    #[serde(default)]
+    pub last_stats_filter: StatsFilter,
     pub last_opening: Openings,
     pub last_variation: Variation,
     pub last_opening_side: Option<OpeningSide>,
+    #[serde(default)]
+    pub last_position_pattern: String,
+    #[serde(default)]
+    pub last_opening_line: String,
+    #[serde(default)]
+    pub last_order: PuzzleOrder,
+    #[serde(default = "default_level_window")]
+    pub last_level_window: i32,
+    // Only read to move the presets saved before there were profiles into the database
+    #[serde(default)]
+    pub saved_searches: Vec<SearchPreset>,
+    #[serde(default = "default_profile")]
+    pub active_profile: i32,
+    #[serde(default)]
+    pub board_display: BoardDisplay,
+    #[serde(default = "default_fade_seconds")]
+    pub fade_seconds: u64,
 }
 
 impl ::std::default::Default for OfflinePuzzlesConfig {
@@ -62,14 +125,46 @@ impl ::std::default::Default for OfflinePuzzlesConfig {
             export_pgs: 50,
             last_min_rating: 0,
             last_max_rating: 1000,
+            last_min_moves: default_min_moves(),
+            last_max_moves: default_max_moves(),
             last_theme: TaticsThemes::All,
-            last_opening: Openings::Any,
+            last_theme_filter: ThemeFilter::default(),
+            last_stats_filter: StatsFilter::default(),
+            last_opening: Openings::ANY,
             last_variation: Variation::ANY,
             last_opening_side: Some(OpeningSide::Any),
+            last_position_pattern: String::new(),
+            last_opening_line: String::new(),
+            last_order: PuzzleOrder::Random,
+            last_level_window: default_level_window(),
+            saved_searches: Vec::new(),
+            active_profile: default_profile(),
+            board_display: BoardDisplay::Normal,
+            fade_seconds: default_fade_seconds(),
         }
     }
 }
 
+fn default_min_moves() -> i32 {
+    1
+}
+
+fn default_max_moves() -> i32 {
+    PuzzleFilter::MAX_MOVES
+}
+
+fn default_level_window() -> i32 {
+    150
+}
+
+fn default_profile() -> i32 {
+    db::DEFAULT_PROFILE
+}
+
+fn default_fade_seconds() -> u64 {
+    5
+}
+
 pub fn load_config() -> OfflinePuzzlesConfig {
     let config;
     let file = std::fs::File::open("settings.json");
@@ -206,3 +301,33 @@ pub struct Puzzle {
     #[serde(default)]
     pub opening: String,
 }
+
+impl Puzzle {
+    // The "moves" field has the opponent's last move followed by the
+    // solution, alternating between the player's and the opponent's moves.
+    pub fn player_moves(&self) -> usize {
+        self.moves.split_whitespace().count() / 2
+    }
+
+    // The side the user plays: the FEN is the position before the
+    // opponent's setup move, so it's the side NOT to move in it.
+    pub fn player_side(&self) -> Option<Color> {
+        match self.fen.split_whitespace().nth(1)? {
+            "w" => Some(Color::Black),
+            "b" => Some(Color::White),
+            _ => None,
+        }
+    }
+
+    // The position the user has to solve, the opponent's last move
+    // (the first in the "moves" field) is applied to the FEN.
+    pub fn start_position(&self) -> Option<Board> {
+        let board = Board::from_str(&self.fen).ok()?;
+        let first_move = self.moves.split_whitespace().next()?;
+        let movement = ChessMove::new(
+            Square::from_str(first_move.get(0..2)?).ok()?,
+            Square::from_str(first_move.get(2..4)?).ok()?,
+            PuzzleTab::check_promotion(first_move));
+        Some(board.make_move_new(movement))
+    }
+}
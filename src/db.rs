@@ -1,15 +1,118 @@
-use diesel::sqlite::SqliteConnection;
+use diesel::sqlite::{Sqlite, SqliteConnection};
+use diesel::connection::DefaultLoadingMode;
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Text};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use dotenvy::dotenv;
 use std::env;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::models::NewFavorite;
-use crate::schema::favs;
-use crate::schema::favs::dsl::*;
+use crate::models::{AttemptRecord, NewAttempt, NewFavorite, NewProfile, NewPuzzle, NewPuzzleOpening, NewPuzzleSource, NewPuzzleTheme, NewRating, NewReview, NewRushRun, NewSavedSearch, NewStreak};
+use crate::schema::{attempts, favs, profiles, puzzle_openings, puzzle_source, puzzle_themes, puzzles, ratings, reviews, rush_runs, saved_searches, streaks};
 use crate::config::Puzzle;
+use crate::dump::PuzzleDump;
 
-use crate::search_tab::{TaticsThemes, OpeningSide};
-use crate::openings::{Openings, Variation};
+use crate::search_tab::{SearchBase, SearchPreset, SearchScan};
+use crate::puzzles::PuzzleAttempt;
+use crate::rating::UserRating;
+use crate::review::ReviewSchedule;
+
+// Existing databases are brought up to date when the app starts
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+// Rows per INSERT when importing the csv, low enough to stay under
+// the limit of bound parameters of older SQLite versions.
+const IMPORT_BATCH_SIZE: usize = 90;
+
+// The profile created by the migration, which owns everything saved before there were profiles
+pub const DEFAULT_PROFILE: i32 = 1;
+
+// The favorites, attempts, ratings, reviews, runs and saved searches are all
+// read and written for this profile. It's set at startup and when switching
+// profiles in the settings, and read from the search thread too.
+static ACTIVE_PROFILE: AtomicI32 = AtomicI32::new(DEFAULT_PROFILE);
+
+pub fn active_profile() -> i32 {
+    ACTIVE_PROFILE.load(Ordering::Relaxed)
+}
+
+pub fn set_active_profile(profile_id: i32) {
+    ACTIVE_PROFILE.store(profile_id, Ordering::Relaxed);
+}
+
+// The columns of the favs, puzzles and reviews tables that make a Puzzle
+macro_rules! puzzle_columns {
+    ($table:ident) => {
+        ($table::puzzle_id, $table::fen, $table::moves, $table::rating, $table::rd,
+            $table::popularity, $table::nb_plays, $table::themes, $table::game_url, $table::opening_tags)
+    };
+}
+
+// The puzzles table has its themes and opening tags indexed in puzzle_themes
+// and puzzle_openings, the favorites are few enough to be matched with LIKE.
+macro_rules! has_theme {
+    (puzzles, $theme:expr) => {
+        puzzles::puzzle_id.eq_any(puzzle_themes::table
+            .filter(puzzle_themes::theme.eq($theme))
+            .select(puzzle_themes::puzzle_id))
+    };
+    ($table:ident, $theme:expr) => {
+        $table::themes.like(String::from("%") + $theme + "%")
+    };
+}
+
+macro_rules! has_opening_tag {
+    (puzzles, $opening_tag:expr) => {
+        puzzles::puzzle_id.eq_any(puzzle_openings::table
+            .filter(puzzle_openings::opening_tag.eq($opening_tag))
+            .select(puzzle_openings::puzzle_id))
+    };
+    ($table:ident, $opening_tag:expr) => {
+        $table::opening_tags.like(String::from("%") + $opening_tag + "%")
+    };
+}
+
+// The favs and puzzles tables have the same puzzle columns, so the search is
+// written only once and expanded for whichever of them we're querying.
+// The query only narrows the rows down, the exact matching is done
+// by the PuzzleFilter.
+macro_rules! filtered_table {
+    ($table:ident, $filter:expr) => {{
+        let mut query = $table::table.into_boxed::<Sqlite>()
+            .filter($table::rating.between($filter.min_rating, $filter.max_rating))
+            .filter($table::popularity.between($filter.stats.min_popularity, $filter.stats.max_popularity))
+            .filter($table::rd.ge($filter.stats.min_rd))
+            .filter($table::nb_plays.ge($filter.stats.min_plays));
+        for theme in &$filter.themes.all_of {
+            query = query.filter(has_theme!($table, theme.get_tag_name()));
+        }
+        if let Some(opening_tag) = &$filter.opening_tag {
+            query = query.filter(has_opening_tag!($table, opening_tag));
+        }
+        query
+    }};
+}
+
+// The rows come in insertion order (the order of the csv for the puzzles),
+// so the same search with the same seed samples the same puzzles.
+macro_rules! search_table {
+    ($table:ident, $conn:expr, $scan:expr $(, $scope:expr)?) => {{
+        let total = filtered_table!($table, $scan.filter())$(.filter($scope))?
+            .count().get_result::<i64>($conn)?.max(1) as u64;
+        let rows = filtered_table!($table, $scan.filter())$(.filter($scope))?
+            .select(puzzle_columns!($table))
+            .order(diesel::dsl::sql::<BigInt>("rowid"))
+            .load_iter::<Puzzle, DefaultLoadingMode>($conn)?;
+        for puzzle in rows.flatten() {
+            let percent = ($scan.progress.scanned as u64 + 1) * 100 / total;
+            if !$scan.check(puzzle, percent) {
+                break;
+            }
+        }
+        Ok(())
+    }};
+}
 
 pub fn establish_connection() -> SqliteConnection {
     dotenv().ok();
@@ -19,61 +122,364 @@ pub fn establish_connection() -> SqliteConnection {
         .unwrap_or_else(|_| panic!("Error connecting to {}", database_url))
 }
 
-pub fn get_favorites(min_rating: i32, max_rating: i32, theme: TaticsThemes, opening: Openings, variation: Variation, op_side: Option<OpeningSide>, result_limit: usize) -> Option<Vec<Puzzle>> {
+pub fn run_migrations() {
     let mut conn = establish_connection();
-    let results;
-    let theme_filter = String::from("%") + theme.get_tag_name() + "%";
-    let limit = result_limit as i64;
-    if opening == Openings::Any {
-        results = favs
-            .filter(rating.between(min_rating, max_rating))
-            .filter(themes.like(theme_filter))
-            .limit(limit)
-            .load::<Puzzle>(&mut conn);
-    } else {
-        let opening_tag: &str = if variation.name != Variation::ANY_STR {
-            &variation.name
-        } else {
-            opening.get_field_name()
-        };
-        let opening_filter = opening_tags.like(String::from("%") + &opening_tag + "%");
-        let side = match op_side {
-            None => OpeningSide::Any,
-            Some(x) => x
-        };
-        if side == OpeningSide::White {
-            results = favs
-                .filter(rating.between(min_rating, max_rating))
-                .filter(themes.like(theme_filter))
-                .filter(opening_filter)
-                .filter(game_url.like("%black%"))
-                .limit(limit)
-                .load::<Puzzle>(&mut conn);
-        } else if side == OpeningSide::Black {
-            results = favs
-                .filter(rating.between(min_rating, max_rating))
-                .filter(themes.like(theme_filter))
-                .filter(opening_filter)
-                .filter(game_url.not_like("%black%"))
-                .limit(limit)
-                .load::<Puzzle>(&mut conn);
-        } else {
-            results = favs
-                .filter(rating.between(min_rating, max_rating))
-                .filter(themes.like(theme_filter))
-                .filter(opening_filter)
-                .limit(limit)
-                .load::<Puzzle>(&mut conn);
-        }
-    }
-    results.ok()
+    if let Err(e) = conn.run_pending_migrations(MIGRATIONS) {
+        eprintln!("Error updating the database: {}", e);
+    }
+}
+
+pub fn search_puzzles(base: SearchBase, scan: &mut SearchScan) -> QueryResult<()> {
+    let mut conn = establish_connection();
+    match base {
+        SearchBase::Favorites => search_table!(favs, &mut conn, scan, favs::profile_id.eq(active_profile())),
+        SearchBase::Lichess | SearchBase::AtMyLevel => search_table!(puzzles, &mut conn, scan),
+    }
+}
+
+pub fn count_puzzles() -> u64 {
+    let mut conn = establish_connection();
+    puzzles::table
+        .count()
+        .get_result::<i64>(&mut conn)
+        .map_or(0, |count| count as u64)
+}
+
+// The distinct opening tags of the imported puzzles and how many puzzles have them
+pub fn opening_tag_counts() -> Option<Vec<(String, i64)>> {
+    let mut conn = establish_connection();
+    puzzles::table
+        .group_by(puzzles::opening_tags)
+        .select((puzzles::opening_tags, diesel::dsl::count_star()))
+        .load::<(String, i64)>(&mut conn)
+        .ok()
+}
+
+pub fn has_imported_puzzles() -> bool {
+    let mut conn = establish_connection();
+    puzzles::table
+        .select(puzzles::puzzle_id)
+        .first::<String>(&mut conn)
+        .is_ok()
+}
+
+// The puzzles table is only ever filled from one csv at a time, so there's a single row
+const PUZZLE_SOURCE_ID: i32 = 1;
+
+// Seconds since the epoch of the last change to the file, 0 if we can't tell
+pub fn file_modified_at(path: &str) -> i64 {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+fn save_puzzle_source(conn: &mut SqliteConnection, csv_path: &str) -> QueryResult<usize> {
+    diesel::replace_into(puzzle_source::table)
+        .values(NewPuzzleSource {
+            id: PUZZLE_SOURCE_ID,
+            csv_path,
+            modified_at: file_modified_at(csv_path),
+        })
+        .execute(conn)
+}
+
+// The path and modification time of the csv the puzzles were imported or merged from
+pub fn puzzle_source() -> Option<(String, i64)> {
+    let mut conn = establish_connection();
+    puzzle_source::table
+        .select((puzzle_source::csv_path, puzzle_source::modified_at))
+        .first::<(String, i64)>(&mut conn)
+        .ok()
+}
+
+// Replaces the contents of the puzzles table with the lichess csv,
+// returns the number of puzzles imported. The percentage of the file
+// read so far is reported as it goes.
+pub fn import_puzzles(csv_path: &str, report: &mut dyn FnMut(u64)) -> Option<usize> {
+    let mut dump = PuzzleDump::open(csv_path).ok()?;
+
+    let mut conn = establish_connection();
+    let result = conn.transaction::<usize, diesel::result::Error, _>(|conn| {
+        diesel::delete(puzzles::table).execute(conn)?;
+        diesel::delete(puzzle_themes::table).execute(conn)?;
+        diesel::delete(puzzle_openings::table).execute(conn)?;
+        save_puzzle_source(conn, csv_path)?;
+        let mut imported = 0;
+        let mut percent = 0;
+        let mut batch: Vec<Puzzle> = Vec::with_capacity(IMPORT_BATCH_SIZE);
+        let mut record = csv::StringRecord::new();
+        while let Ok(true) = dump.reader.read_record(&mut record) {
+            if let Ok(puzzle) = record.deserialize::<Puzzle>(None) {
+                batch.push(puzzle);
+            }
+            if batch.len() == IMPORT_BATCH_SIZE {
+                imported += insert_puzzles(conn, &batch)?;
+                batch.clear();
+            }
+            if dump.percent_read() != percent {
+                percent = dump.percent_read();
+                report(percent);
+            }
+        }
+        imported += insert_puzzles(conn, &batch)?;
+        Ok(imported)
+    });
+    result.ok()
+}
+
+// What changed in the puzzles table when merging a newer lichess file
+#[derive(Debug, Clone, Default)]
+pub struct MergeReport {
+    pub added: usize,
+    pub updated: usize,
+    // Not deleted by the merge, the user confirms it first
+    pub removed: Vec<String>,
+    pub favs_refreshed: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum MergeError {
+    Open,
+    // A row couldn't be read, e.g. from a truncated or corrupt download
+    Parse(String),
+    // The file has far fewer puzzles than the database, so it's probably
+    // not the lichess file and most of the puzzles would look removed.
+    TooFewPuzzles { found: usize, existing: usize },
+    Database(String),
+}
+
+impl From<diesel::result::Error> for MergeError {
+    fn from(error: diesel::result::Error) -> Self {
+        MergeError::Database(error.to_string())
+    }
+}
+
+// A merge is refused if the new file has less than this fraction of the puzzles we have
+const MIN_MERGE_RATIO: usize = 2;
+
+#[derive(QueryableByName)]
+struct PuzzleId {
+    #[diesel(sql_type = Text)]
+    puzzle_id: String,
+}
+
+// Updates the puzzles table with a newer lichess csv: new puzzles are added,
+// the stats of the existing ones are updated and the ones missing from the
+// new file are reported, to be removed with remove_puzzles once the user
+// confirms it. The favorites get the new stats too. Nothing is changed if
+// the file can't be read to the end. The percentage of the file read so far
+// is reported as it goes.
+pub fn merge_puzzles(csv_path: &str, report_progress: &mut dyn FnMut(u64)) -> Result<MergeReport, MergeError> {
+    let mut dump = PuzzleDump::open(csv_path).map_err(|_| MergeError::Open)?;
+
+    let mut conn = establish_connection();
+    conn.transaction::<MergeReport, MergeError, _>(|conn| {
+        let existing = puzzles::table.count().get_result::<i64>(conn)? as usize;
+
+        // Only the ids are kept here, to find the removed puzzles at the end
+        diesel::sql_query("CREATE TEMP TABLE IF NOT EXISTS dump_ids (puzzle_id TEXT PRIMARY KEY NOT NULL)").execute(conn)?;
+        diesel::sql_query("DELETE FROM dump_ids").execute(conn)?;
+
+        let mut report = MergeReport::default();
+        let mut found = 0;
+        let mut percent = 0;
+        let mut batch: Vec<Puzzle> = Vec::with_capacity(IMPORT_BATCH_SIZE);
+        let mut record = csv::StringRecord::new();
+        for index in 0.. {
+            let result = dump.reader.read_record(&mut record)
+                .and_then(|more| if more { record.deserialize::<Puzzle>(None).map(Some) } else { Ok(None) });
+            match result {
+                Ok(Some(puzzle)) => batch.push(puzzle),
+                Ok(None) => break,
+                // The newer files start with a header row
+                Err(_) if index == 0 => continue,
+                Err(e) => return Err(MergeError::Parse(e.to_string())),
+            }
+            found += 1;
+            if batch.len() == IMPORT_BATCH_SIZE {
+                merge_batch(conn, &batch, &mut report)?;
+                batch.clear();
+            }
+            if dump.percent_read() != percent {
+                percent = dump.percent_read();
+                report_progress(percent);
+            }
+        }
+        merge_batch(conn, &batch, &mut report)?;
+
+        if found == 0 || found * MIN_MERGE_RATIO < existing {
+            return Err(MergeError::TooFewPuzzles { found, existing });
+        }
+
+        report.removed = diesel::sql_query("SELECT puzzle_id FROM puzzles WHERE puzzle_id NOT IN (SELECT puzzle_id FROM dump_ids)")
+            .load::<PuzzleId>(conn)?
+            .into_iter()
+            .map(|row| row.puzzle_id)
+            .collect();
+        diesel::sql_query("DROP TABLE dump_ids").execute(conn)?;
+        save_puzzle_source(conn, csv_path)?;
+
+        // The favorites of every profile are refreshed
+        let fav_ids = favs::table.select(favs::puzzle_id).distinct().load::<String>(conn)?;
+        for id in fav_ids {
+            if let Ok(puzzle) = puzzles::table.filter(puzzles::puzzle_id.eq(&id)).first::<Puzzle>(conn) {
+                report.favs_refreshed += diesel::update(favs::table.filter(favs::puzzle_id.eq(&id)))
+                    .set((
+                        favs::rating.eq(puzzle.rating),
+                        favs::rd.eq(puzzle.rating_deviation),
+                        favs::popularity.eq(puzzle.popularity),
+                        favs::nb_plays.eq(puzzle.nb_plays),
+                    ))
+                    .execute(conn)?;
+            }
+        }
+        Ok(report)
+    })
+}
+
+// Deletes the puzzles a merge found missing from the newer file, returns how many were deleted
+pub fn remove_puzzles(ids: &[String]) -> Option<usize> {
+    let mut conn = establish_connection();
+    let result = conn.transaction::<usize, diesel::result::Error, _>(|conn| {
+        let mut removed = 0;
+        for chunk in ids.chunks(IDS_PER_QUERY) {
+            removed += diesel::delete(puzzles::table.filter(puzzles::puzzle_id.eq_any(chunk))).execute(conn)?;
+            diesel::delete(puzzle_themes::table.filter(puzzle_themes::puzzle_id.eq_any(chunk))).execute(conn)?;
+            diesel::delete(puzzle_openings::table.filter(puzzle_openings::puzzle_id.eq_any(chunk))).execute(conn)?;
+        }
+        Ok(removed)
+    });
+    match result {
+        Ok(removed) => Some(removed),
+        Err(e) => {
+            eprintln!("Error removing the puzzles: {}", e);
+            None
+        }
+    }
+}
+
+fn merge_batch(conn: &mut SqliteConnection, batch: &[Puzzle], report: &mut MergeReport) -> QueryResult<()> {
+    if batch.is_empty() {
+        return Ok(());
+    }
+    let placeholders = vec!["(?)"; batch.len()].join(",");
+    let mut ids_query = diesel::sql_query(String::from("INSERT OR IGNORE INTO dump_ids (puzzle_id) VALUES ") + &placeholders).into_boxed();
+    for puzzle in batch {
+        ids_query = ids_query.bind::<Text, _>(&puzzle.puzzle_id);
+    }
+    ids_query.execute(conn)?;
+
+    let ids: Vec<&str> = batch.iter().map(|puzzle| puzzle.puzzle_id.as_str()).collect();
+    let existing = puzzles::table.filter(puzzles::puzzle_id.eq_any(&ids)).count().get_result::<i64>(conn)? as usize;
+
+    // The new puzzles are added and the stats of the existing ones updated in one go,
+    // the rows it changes are the puzzles added plus the ones whose stats actually changed.
+    let placeholders = vec!["(?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"; batch.len()].join(",");
+    let mut upsert = diesel::sql_query(String::from("INSERT INTO puzzles \
+            (puzzle_id, fen, moves, rating, rd, popularity, nb_plays, themes, game_url, opening_tags) VALUES ") + &placeholders + " \
+            ON CONFLICT (puzzle_id) DO UPDATE SET \
+                rating = excluded.rating, rd = excluded.rd, popularity = excluded.popularity, nb_plays = excluded.nb_plays \
+            WHERE puzzles.rating != excluded.rating OR puzzles.rd != excluded.rd OR \
+                puzzles.popularity != excluded.popularity OR puzzles.nb_plays != excluded.nb_plays")
+        .into_boxed();
+    for puzzle in batch {
+        upsert = upsert
+            .bind::<Text, _>(&puzzle.puzzle_id)
+            .bind::<Text, _>(&puzzle.fen)
+            .bind::<Text, _>(&puzzle.moves)
+            .bind::<Integer, _>(puzzle.rating)
+            .bind::<Integer, _>(puzzle.rating_deviation)
+            .bind::<Integer, _>(puzzle.popularity)
+            .bind::<Integer, _>(puzzle.nb_plays)
+            .bind::<Text, _>(&puzzle.themes)
+            .bind::<Text, _>(&puzzle.game_url)
+            .bind::<Text, _>(&puzzle.opening);
+    }
+    let changed = upsert.execute(conn)?;
+    insert_tags(conn, batch)?;
+
+    let added = batch.len().saturating_sub(existing);
+    report.added += added;
+    report.updated += changed.saturating_sub(added);
+    Ok(())
+}
+
+fn insert_puzzles(conn: &mut SqliteConnection, batch: &[Puzzle]) -> QueryResult<usize> {
+    if batch.is_empty() {
+        return Ok(0);
+    }
+    let new_puzzles: Vec<NewPuzzle> = batch.iter().map(|puzzle| NewPuzzle {
+        puzzle_id: &puzzle.puzzle_id,
+        fen: &puzzle.fen,
+        moves: &puzzle.moves,
+        rating: puzzle.rating,
+        rd: puzzle.rating_deviation,
+        popularity: puzzle.popularity,
+        nb_plays: puzzle.nb_plays,
+        themes: &puzzle.themes,
+        game_url: &puzzle.game_url,
+        opening_tags: &puzzle.opening,
+    }).collect();
+
+    let inserted = diesel::insert_or_ignore_into(puzzles::table)
+        .values(&new_puzzles)
+        .execute(conn)?;
+    insert_tags(conn, batch)?;
+    Ok(inserted)
+}
+
+// Fills puzzle_themes and puzzle_openings, the rows of the puzzles that
+// were already there are ignored since their tags don't change.
+fn insert_tags(conn: &mut SqliteConnection, batch: &[Puzzle]) -> QueryResult<()> {
+    let themes: Vec<NewPuzzleTheme> = batch.iter().flat_map(|puzzle| {
+        puzzle.themes.split_whitespace().map(|theme| NewPuzzleTheme { puzzle_id: &puzzle.puzzle_id, theme })
+    }).collect();
+    for chunk in themes.chunks(TAGS_PER_INSERT) {
+        diesel::insert_or_ignore_into(puzzle_themes::table).values(chunk).execute(conn)?;
+    }
+    let openings: Vec<NewPuzzleOpening> = batch.iter().flat_map(|puzzle| {
+        puzzle.opening.split_whitespace().map(|opening_tag| NewPuzzleOpening { puzzle_id: &puzzle.puzzle_id, opening_tag })
+    }).collect();
+    for chunk in openings.chunks(TAGS_PER_INSERT) {
+        diesel::insert_or_ignore_into(puzzle_openings::table).values(chunk).execute(conn)?;
+    }
+    Ok(())
+}
+
+// Each row of the puzzle_themes and puzzle_openings tables takes two bound parameters
+const TAGS_PER_INSERT: usize = IDS_PER_QUERY / 2;
+
+// SQLite limits the number of bound parameters, so the ids are queried in chunks
+const IDS_PER_QUERY: usize = 500;
+
+// Looks up the puzzles in the favorites and, if there are any, in the imported puzzles
+pub fn find_puzzles(ids: &[String]) -> Vec<Puzzle> {
+    let mut conn = establish_connection();
+    let mut found = Vec::new();
+    for chunk in ids.chunks(IDS_PER_QUERY) {
+        if let Ok(puzzles) = favs::table
+                .filter(favs::profile_id.eq(active_profile()))
+                .filter(favs::puzzle_id.eq_any(chunk))
+                .select(puzzle_columns!(favs))
+                .load::<Puzzle>(&mut conn) {
+            found.extend(puzzles);
+        }
+        if let Ok(puzzles) = puzzles::table.filter(puzzles::puzzle_id.eq_any(chunk)).load::<Puzzle>(&mut conn) {
+            found.extend(puzzles);
+        }
+    }
+    found
 }
 
 pub fn is_favorite(id: &str) -> bool {
     let mut conn = establish_connection();
-    let results = favs
-        .filter(puzzle_id.eq(id))
-        .first::<Puzzle>(&mut conn);
+    let results = favs::table
+        .filter(favs::profile_id.eq(active_profile()))
+        .filter(favs::puzzle_id.eq(id))
+        .select(favs::puzzle_id)
+        .first::<String>(&mut conn);
     if results.is_ok() {
         return true;
     }
@@ -82,17 +488,23 @@ pub fn is_favorite(id: &str) -> bool {
 
 pub fn toggle_favorite(puzzle: Puzzle) {
     let mut conn = establish_connection();
-    let is_fav = favs
-        .filter(puzzle_id.eq(&puzzle.puzzle_id))
-        .first::<Puzzle>(&mut conn).is_ok();
+    let profile_id = active_profile();
+    let is_fav = favs::table
+        .filter(favs::profile_id.eq(profile_id))
+        .filter(favs::puzzle_id.eq(&puzzle.puzzle_id))
+        .select(favs::puzzle_id)
+        .first::<String>(&mut conn).is_ok();
 
     if is_fav {
-        diesel::delete(favs::table)
-            .filter(puzzle_id.eq(&puzzle.puzzle_id))
-            .execute(&mut conn)
-            .expect("Error removing favorite");
+        if let Err(e) = diesel::delete(favs::table)
+                .filter(favs::profile_id.eq(profile_id))
+                .filter(favs::puzzle_id.eq(&puzzle.puzzle_id))
+                .execute(&mut conn) {
+            eprintln!("Error removing the favorite: {}", e);
+        }
     } else {
         let new_fav = NewFavorite {
+            profile_id,
             puzzle_id: &puzzle.puzzle_id,
             fen: &puzzle.fen,
             moves: &puzzle.moves,
@@ -105,9 +517,370 @@ pub fn toggle_favorite(puzzle: Puzzle) {
             opening_tags: &puzzle.opening,
         };
 
-        diesel::insert_into(favs::table)
-            .values(&new_fav)
-            .execute(&mut conn)
-            .expect("Error saving new favorite");
+        if let Err(e) = diesel::insert_into(favs::table)
+                .values(&new_fav)
+                .execute(&mut conn) {
+            eprintln!("Error saving the favorite: {}", e);
+        }
+    }
+}
+
+fn unix_time() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs() as i64).unwrap_or(0)
+}
+
+pub fn save_attempt(attempt: &PuzzleAttempt, solved: bool) {
+    let mut conn = establish_connection();
+    let new_attempt = NewAttempt {
+        puzzle_id: &attempt.puzzle_id,
+        attempted_at: unix_time(),
+        solved,
+        wrong_moves: attempt.wrong_moves,
+        hints: attempt.hints,
+        time_taken_ms: attempt.started.elapsed().as_millis() as i64,
+        puzzle_rating: attempt.puzzle_rating,
+        themes: &attempt.themes,
+        opening_tags: &attempt.opening,
+        profile_id: active_profile(),
+    };
+
+    if let Err(e) = diesel::insert_into(attempts::table)
+            .values(&new_attempt)
+            .execute(&mut conn) {
+        eprintln!("Error saving the puzzle attempt: {}", e);
+    }
+}
+
+// The last entry of the rating history, or a new player's rating.
+pub fn current_rating() -> UserRating {
+    let mut conn = establish_connection();
+    ratings::table
+        .filter(ratings::profile_id.eq(active_profile()))
+        .select((ratings::rating, ratings::deviation, ratings::volatility))
+        .order(ratings::id.desc())
+        .first::<(f64, f64, f64)>(&mut conn)
+        .map(|(rating, deviation, volatility)| UserRating { rating, deviation, volatility })
+        .unwrap_or_default()
+}
+
+pub fn attempt_records() -> Vec<AttemptRecord> {
+    let mut conn = establish_connection();
+    attempts::table
+        .filter(attempts::profile_id.eq(active_profile()))
+        .order(attempts::id.asc())
+        .load::<AttemptRecord>(&mut conn)
+        .unwrap_or_default()
+}
+
+// (time, rating) after each rated attempt, oldest first
+pub fn rating_history() -> Vec<(i64, f64)> {
+    let mut conn = establish_connection();
+    ratings::table
+        .filter(ratings::profile_id.eq(active_profile()))
+        .select((ratings::rated_at, ratings::rating))
+        .order(ratings::id.asc())
+        .load::<(i64, f64)>(&mut conn)
+        .unwrap_or_default()
+}
+
+pub fn save_rating(puzzle_id: &str, rating: &UserRating) {
+    let mut conn = establish_connection();
+    let new_rating = NewRating {
+        rated_at: unix_time(),
+        puzzle_id,
+        rating: rating.rating,
+        deviation: rating.deviation,
+        volatility: rating.volatility,
+        profile_id: active_profile(),
+    };
+
+    if let Err(e) = diesel::insert_into(ratings::table)
+            .values(&new_rating)
+            .execute(&mut conn) {
+        eprintln!("Error saving the rating: {}", e);
+    }
+}
+
+// Failed puzzles enter the review queue, and the ones already in it
+// are rescheduled according to the result.
+pub fn schedule_review(puzzle: &Puzzle, solved: bool) {
+    let mut conn = establish_connection();
+    let profile_id = active_profile();
+    let schedule = reviews::table
+        .filter(reviews::profile_id.eq(profile_id))
+        .filter(reviews::puzzle_id.eq(&puzzle.puzzle_id))
+        .select((reviews::ease, reviews::interval_days, reviews::repetitions))
+        .first::<(f64, i32, i32)>(&mut conn)
+        .map(|(ease, interval_days, repetitions)| ReviewSchedule { ease, interval_days, repetitions });
+
+    let result = match schedule {
+        Ok(schedule) => {
+            let next = schedule.next(solved);
+            diesel::update(reviews::table
+                    .filter(reviews::profile_id.eq(profile_id))
+                    .filter(reviews::puzzle_id.eq(&puzzle.puzzle_id)))
+                .set((
+                    reviews::ease.eq(next.ease),
+                    reviews::interval_days.eq(next.interval_days),
+                    reviews::repetitions.eq(next.repetitions),
+                    reviews::due_at.eq(next.due_at(unix_time())),
+                ))
+                .execute(&mut conn)
+        } Err(_) if !solved => {
+            let next = ReviewSchedule::default().next(false);
+            let new_review = NewReview {
+                profile_id,
+                puzzle_id: &puzzle.puzzle_id,
+                fen: &puzzle.fen,
+                moves: &puzzle.moves,
+                rating: puzzle.rating,
+                rd: puzzle.rating_deviation,
+                popularity: puzzle.popularity,
+                nb_plays: puzzle.nb_plays,
+                themes: &puzzle.themes,
+                game_url: &puzzle.game_url,
+                opening_tags: &puzzle.opening,
+                ease: next.ease,
+                interval_days: next.interval_days,
+                repetitions: next.repetitions,
+                due_at: next.due_at(unix_time()),
+            };
+            diesel::insert_into(reviews::table)
+                .values(&new_review)
+                .execute(&mut conn)
+        } Err(_) => Ok(0),
+    };
+    if let Err(e) = result {
+        eprintln!("Error scheduling the puzzle review: {}", e);
+    }
+}
+
+pub fn due_reviews() -> Vec<Puzzle> {
+    let mut conn = establish_connection();
+    reviews::table
+        .filter(reviews::profile_id.eq(active_profile()))
+        .filter(reviews::due_at.le(unix_time()))
+        .order(reviews::due_at.asc())
+        .select(puzzle_columns!(reviews))
+        .load::<Puzzle>(&mut conn)
+        .unwrap_or_default()
+}
+
+pub fn count_due_reviews() -> i64 {
+    let mut conn = establish_connection();
+    reviews::table
+        .filter(reviews::profile_id.eq(active_profile()))
+        .filter(reviews::due_at.le(unix_time()))
+        .count()
+        .get_result(&mut conn)
+        .unwrap_or(0)
+}
+
+pub fn save_rush_run(minutes: i32, score: i32) {
+    let mut conn = establish_connection();
+    let new_run = NewRushRun {
+        finished_at: unix_time(),
+        minutes,
+        score,
+        profile_id: active_profile(),
+    };
+
+    if let Err(e) = diesel::insert_into(rush_runs::table)
+            .values(&new_run)
+            .execute(&mut conn) {
+        eprintln!("Error saving the puzzle rush score: {}", e);
+    }
+}
+
+// The personal best for runs of this length, 0 if there's none yet
+pub fn rush_best(minutes: i32) -> i32 {
+    let mut conn = establish_connection();
+    rush_runs::table
+        .filter(rush_runs::profile_id.eq(active_profile()))
+        .filter(rush_runs::minutes.eq(minutes))
+        .select(diesel::dsl::max(rush_runs::score))
+        .first::<Option<i32>>(&mut conn)
+        .ok()
+        .flatten()
+        .unwrap_or(0)
+}
+
+pub fn save_streak(length: i32) {
+    let mut conn = establish_connection();
+    let new_streak = NewStreak {
+        finished_at: unix_time(),
+        length,
+        profile_id: active_profile(),
+    };
+
+    if let Err(e) = diesel::insert_into(streaks::table)
+            .values(&new_streak)
+            .execute(&mut conn) {
+        eprintln!("Error saving the streak: {}", e);
+    }
+}
+
+pub fn streak_best() -> i32 {
+    let mut conn = establish_connection();
+    streaks::table
+        .filter(streaks::profile_id.eq(active_profile()))
+        .select(diesel::dsl::max(streaks::length))
+        .first::<Option<i32>>(&mut conn)
+        .ok()
+        .flatten()
+        .unwrap_or(0)
+}
+
+pub fn profiles() -> Vec<(i32, String)> {
+    let mut conn = establish_connection();
+    profiles::table
+        .order(profiles::id.asc())
+        .load::<(i32, String)>(&mut conn)
+        .unwrap_or_default()
+}
+
+// Returns the id of the new profile, or None if the name is taken
+pub fn create_profile(name: &str) -> Option<i32> {
+    let mut conn = establish_connection();
+    let result = diesel::insert_into(profiles::table)
+        .values(&NewProfile { name })
+        .execute(&mut conn)
+        .and_then(|_| profiles::table
+            .filter(profiles::name.eq(name))
+            .select(profiles::id)
+            .first::<i32>(&mut conn));
+    match result {
+        Ok(id) => Some(id),
+        Err(e) => {
+            eprintln!("Error creating the profile: {}", e);
+            None
+        }
+    }
+}
+
+// The presets are kept as json, in the order they were saved
+pub fn saved_searches() -> Vec<SearchPreset> {
+    let mut conn = establish_connection();
+    saved_searches::table
+        .filter(saved_searches::profile_id.eq(active_profile()))
+        .order(saved_searches::id.asc())
+        .select(saved_searches::preset)
+        .load::<String>(&mut conn)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|preset| serde_json::from_str(preset).ok())
+        .collect()
+}
+
+pub fn save_searches(presets: &[SearchPreset]) {
+    let profile_id = active_profile();
+    let mut conn = establish_connection();
+    let result = conn.transaction::<(), diesel::result::Error, _>(|conn| {
+        diesel::delete(saved_searches::table.filter(saved_searches::profile_id.eq(profile_id))).execute(conn)?;
+        for preset in presets {
+            let json = serde_json::to_string(preset).unwrap_or_default();
+            diesel::insert_into(saved_searches::table)
+                .values(&NewSavedSearch { profile_id, name: &preset.name, preset: &json })
+                .execute(conn)?;
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        eprintln!("Error saving the search presets: {}", e);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard, Once};
+
+    // The tests that need the database share one, since its location is read from the
+    // environment. Each of them uses its own puzzles or profile so they don't interfere.
+    pub fn use_test_database() {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            let path = env::temp_dir().join(format!("ocp-test-{}.db", std::process::id()));
+            let _ = std::fs::remove_file(&path);
+            env::set_var("DATABASE_URL", &path);
+            run_migrations();
+        });
+    }
+
+    // The active profile is shared by the whole app, so the tests that
+    // switch to their own take turns, until the guard is dropped.
+    pub fn use_test_profile(profile_id: i32) -> MutexGuard<'static, ()> {
+        static PROFILE: Mutex<()> = Mutex::new(());
+        use_test_database();
+        let guard = PROFILE.lock().unwrap_or_else(|e| e.into_inner());
+        set_active_profile(profile_id);
+        guard
+    }
+
+    const CSV_HEADER: &str = "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags\n";
+    const CSV_PUZZLES: &str = "\
+00sHx,q3k1nr/1pp1nQpp/3p4/1P2p3/4P3/B1PP1b2/B5PP/5K2 b k - 0 17,e8d7 a2e6 d7d8 f7f8,1760,80,83,72,mate mateIn2 middlegame short,https://lichess.org/yyznGmXs/black#34,Italian_Game Italian_Game_Classical_Variation
+00sJ9,r3r1k1/p4ppp/2p2n2/1p6/3P1qb1/2NQR3/PPB2PP1/R1B3K1 w - - 5 18,e3g3 e8e1 g1h2 e1c1 a1c1 f4h6 h2g1 h6c1,2671,105,87,325,advantage attraction fork middlegame sacrifice veryLong,https://lichess.org/gyFeQsOE#35,French_Defense French_Defense_Exchange_Variation
+";
+
+    // The only test that fills the puzzles table
+    #[test]
+    fn merge_onto_an_empty_table() {
+        use_test_database();
+        let dir = env::temp_dir().join(format!("ocp-merge-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let csv_path = dir.join("puzzles.csv");
+        let empty_csv_path = dir.join("empty.csv");
+        std::fs::write(&csv_path, String::from(CSV_HEADER) + CSV_PUZZLES).unwrap();
+        std::fs::write(&empty_csv_path, CSV_HEADER).unwrap();
+        let csv_path = csv_path.to_str().unwrap();
+
+        let report = merge_puzzles(csv_path, &mut |_| ()).unwrap();
+        assert_eq!(report.added, 2);
+        assert_eq!(report.updated, 0);
+        assert!(report.removed.is_empty());
+        assert_eq!(count_puzzles(), 2);
+        assert_eq!(puzzle_source().map(|(path, _)| path), Some(csv_path.to_string()));
+
+        // The tags are searched through the index, whole tags only
+        let mut conn = establish_connection();
+        let with_theme = |conn: &mut SqliteConnection, theme: &str| puzzles::table
+            .filter(has_theme!(puzzles, theme))
+            .select(puzzles::puzzle_id)
+            .load::<String>(conn)
+            .unwrap();
+        assert_eq!(with_theme(&mut conn, "fork"), vec!["00sJ9"]);
+        assert_eq!(with_theme(&mut conn, "middlegame").len(), 2);
+        assert!(with_theme(&mut conn, "mateIn").is_empty());
+        let with_opening = puzzles::table
+            .filter(has_opening_tag!(puzzles, "Italian_Game"))
+            .select(puzzles::puzzle_id)
+            .load::<String>(&mut conn)
+            .unwrap();
+        assert_eq!(with_opening, vec!["00sHx"]);
+
+        // Merging the same file again changes nothing
+        let report = merge_puzzles(csv_path, &mut |_| ()).unwrap();
+        assert_eq!((report.added, report.updated, report.removed.len()), (0, 0, 0));
+
+        // Only the puzzles whose stats changed are updated
+        let updated_csv_path = dir.join("updated.csv");
+        std::fs::write(&updated_csv_path, String::from(CSV_HEADER) + &CSV_PUZZLES.replacen(",1760,80,83,72,", ",1774,78,84,90,", 1)).unwrap();
+        let mut percents = Vec::new();
+        let report = merge_puzzles(updated_csv_path.to_str().unwrap(), &mut |percent| percents.push(percent)).unwrap();
+        assert_eq!((report.added, report.updated, report.removed.len()), (0, 1, 0));
+        assert_eq!(percents.last(), Some(&100));
+        let updated = puzzles::table.find("00sHx").first::<Puzzle>(&mut conn).unwrap();
+        assert_eq!((updated.rating, updated.rating_deviation, updated.popularity, updated.nb_plays), (1774, 78, 84, 90));
+
+        // A file without puzzles would remove them all
+        match merge_puzzles(empty_csv_path.to_str().unwrap(), &mut |_| ()) {
+            Err(MergeError::TooFewPuzzles { found, existing }) => assert_eq!((found, existing), (0, 2)),
+            other => panic!("unexpected merge result {:?}", other),
+        }
+        assert_eq!(count_puzzles(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,113 @@
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::rc::Rc;
+
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+
+// Lichess distributes the puzzles as a zstd compressed csv, but we also
+// accept the other usual formats and the plain csv.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None, Zstd, Bzip2, Gzip
+}
+
+impl Compression {
+    // The magic bytes take precedence, the extension is used when
+    // the file is too short to tell.
+    pub fn detect(path: &str, header: &[u8]) -> Compression {
+        if header.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Compression::Zstd
+        } else if header.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else if header.starts_with(&[0x1F, 0x8B]) {
+            Compression::Gzip
+        } else if header.len() >= 4 {
+            Compression::None
+        } else if path.ends_with(".zst") {
+            Compression::Zstd
+        } else if path.ends_with(".bz2") {
+            Compression::Bzip2
+        } else if path.ends_with(".gz") {
+            Compression::Gzip
+        } else {
+            Compression::None
+        }
+    }
+}
+
+// Keeps track of how much of the (possibly compressed) file was read,
+// so we can tell how far along we are without knowing the csv size.
+struct CountingReader<R> {
+    inner: R,
+    bytes_read: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.bytes_read.set(self.bytes_read.get() + read as u64);
+        Ok(read)
+    }
+}
+
+// A csv reader for the lichess puzzles file, decompressing it while reading
+pub struct PuzzleDump {
+    pub reader: csv::Reader<Box<dyn Read>>,
+    bytes_read: Rc<Cell<u64>>,
+    file_size: u64,
+}
+
+impl PuzzleDump {
+    pub fn open(path: &str) -> io::Result<PuzzleDump> {
+        let mut file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+        let mut header = [0; 4];
+        let header_len = file.read(&mut header)?;
+        file.seek(SeekFrom::Start(0))?;
+
+        let bytes_read = Rc::new(Cell::new(0));
+        let counting = BufReader::new(CountingReader { inner: file, bytes_read: bytes_read.clone() });
+        let decoded: Box<dyn Read> = match Compression::detect(path, &header[..header_len]) {
+            Compression::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(counting)?),
+            Compression::Bzip2 => Box::new(MultiBzDecoder::new(counting)),
+            Compression::Gzip => Box::new(MultiGzDecoder::new(counting)),
+            Compression::None => Box::new(counting),
+        };
+        let reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(decoded);
+        Ok(PuzzleDump { reader, bytes_read, file_size })
+    }
+
+    pub fn percent_read(&self) -> u64 {
+        self.bytes_read.get() * 100 / self.file_size.max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_from_the_magic_bytes() {
+        // The extension doesn't matter when there's enough of the file to tell
+        assert_eq!(Compression::detect("puzzles.csv", &[0x28, 0xB5, 0x2F, 0xFD]), Compression::Zstd);
+        assert_eq!(Compression::detect("puzzles.csv", b"BZh9"), Compression::Bzip2);
+        assert_eq!(Compression::detect("puzzles.csv", &[0x1F, 0x8B, 0x08, 0x00]), Compression::Gzip);
+        assert_eq!(Compression::detect("puzzles.csv.zst", b"Puzz"), Compression::None);
+        assert_eq!(Compression::detect("puzzles.csv", b"00sH"), Compression::None);
+    }
+
+    #[test]
+    fn detect_short_files_from_the_extension() {
+        assert_eq!(Compression::detect("puzzles.csv.zst", &[0x28, 0xB5]), Compression::Zstd);
+        assert_eq!(Compression::detect("puzzles.csv.bz2", b""), Compression::Bzip2);
+        assert_eq!(Compression::detect("puzzles.csv.gz", b"a"), Compression::Gzip);
+        assert_eq!(Compression::detect("puzzles.csv", b"a,b"), Compression::None);
+        // The magic bytes still win over the extension
+        assert_eq!(Compression::detect("puzzles.csv.zst", &[0x1F, 0x8B]), Compression::Gzip);
+    }
+}
@@ -0,0 +1,175 @@
+use once_cell::sync::Lazy;
+
+// The ECO code and moves of the opening lines, named like lichess names them,
+// so we can tell which opening tags a line leads to. The files are laid out
+// like the lichess chess-openings repository, one per ECO volume, so they
+// can be replaced with its a.tsv to e.tsv.
+const ECO_TABLES: [&str; 5] = [
+    include_str!("../eco/a.tsv"),
+    include_str!("../eco/b.tsv"),
+    include_str!("../eco/c.tsv"),
+    include_str!("../eco/d.tsv"),
+    include_str!("../eco/e.tsv"),
+];
+
+struct EcoLine {
+    eco: &'static str,
+    // The OpeningTags lichess gives the puzzles from the line: the family, and the variation if it has one
+    tags: String,
+    moves: Vec<String>,
+}
+
+static ECO_LINES: Lazy<Vec<EcoLine>> = Lazy::new(|| {
+    ECO_TABLES.iter().flat_map(|table| table.lines().skip(1)).filter_map(|line| {
+        let mut columns = line.split('\t');
+        let eco = columns.next()?;
+        let name = columns.next()?;
+        let moves = normalize_moves(columns.next()?);
+        Some(EcoLine { eco, tags: lichess_tags(name), moves })
+    }).collect()
+});
+
+// Returns the OpeningTags of the games that reached the moves (e.g. "1.e4 c5 2.Nf3 d6")
+// or are in the ECO range (e.g. "B90-B99" or just "B90"), None if there isn't any.
+// A puzzle is from one of the lines if its OpeningTags are exactly one of these, so a line
+// that is only a family (like "Ruy_Lopez") doesn't take in the variations of that family.
+pub fn opening_tags(query: &str) -> Option<Vec<String>> {
+    let mut tags: Vec<String> = match eco_range(query) {
+        Some((from, to)) => ECO_LINES.iter()
+            .filter(|line| line.eco >= from.as_str() && line.eco <= to.as_str())
+            .map(|line| line.tags.clone())
+            .collect(),
+        None => {
+            let moves = normalize_moves(query);
+            if moves.is_empty() {
+                return None;
+            }
+            let reached: Vec<String> = ECO_LINES.iter()
+                .filter(|line| line.moves.starts_with(&moves))
+                .map(|line| line.tags.clone())
+                .collect();
+            if reached.is_empty() {
+                // Deeper than the lines we know, so it's classified as the longest one it goes through
+                ECO_LINES.iter()
+                    .filter(|line| moves.starts_with(&line.moves))
+                    .max_by_key(|line| line.moves.len())
+                    .map(|line| vec![line.tags.clone()])
+                    .unwrap_or_default()
+            } else {
+                reached
+            }
+        }
+    };
+    tags.sort();
+    tags.dedup();
+    if tags.is_empty() {
+        None
+    } else {
+        Some(tags)
+    }
+}
+
+fn eco_range(query: &str) -> Option<(String, String)> {
+    let query = query.trim().to_ascii_uppercase();
+    let (from, to) = match query.split_once(['-', '–']) {
+        Some((from, to)) => (from.trim().to_string(), to.trim().to_string()),
+        None => (query.clone(), query.clone()),
+    };
+    if is_eco_code(&from) && is_eco_code(&to) {
+        Some((from, to))
+    } else {
+        None
+    }
+}
+
+fn is_eco_code(code: &str) -> bool {
+    let bytes = code.as_bytes();
+    bytes.len() == 3 && (b'A'..=b'E').contains(&bytes[0]) &&
+        bytes[1].is_ascii_digit() && bytes[2].is_ascii_digit()
+}
+
+// "1.e4 c5 2. Nf3+" -> ["e4", "c5", "Nf3"]
+fn normalize_moves(moves: &str) -> Vec<String> {
+    moves.split_whitespace()
+        .map(|token| token.rsplit('.').next().unwrap_or_default())
+        .map(|token| token.trim_end_matches(['+', '#', '!', '?']))
+        .filter(|token| !token.is_empty())
+        .map(|token| token.replace('0', "O"))
+        .collect()
+}
+
+// "Sicilian Defense: Najdorf Variation, English Attack" -> "Sicilian_Defense Sicilian_Defense_Najdorf_Variation",
+// as lichess only tags the family and the variation.
+fn lichess_tags(name: &str) -> String {
+    match name.split_once(':') {
+        Some((family, rest)) => {
+            let variation = rest.split(',').next().unwrap_or_default();
+            lichess_tag(family) + " " + &lichess_tag(&(String::from(family) + " " + variation))
+        }
+        None => lichess_tag(name),
+    }
+}
+
+// "King's Gambit Declined" -> "Kings_Gambit_Declined"
+fn lichess_tag(name: &str) -> String {
+    name.split_whitespace()
+        .map(|word| word.chars().filter(|c| c.is_alphanumeric() || *c == '-').collect::<String>())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<String>>()
+        .join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(query: &str) -> Vec<String> {
+        opening_tags(query).unwrap_or_default()
+    }
+
+    #[test]
+    fn eco_ranges() {
+        assert_eq!(eco_range("B90-B99"), Some((String::from("B90"), String::from("B99"))));
+        assert_eq!(eco_range(" b90 – b99 "), Some((String::from("B90"), String::from("B99"))));
+        assert_eq!(eco_range("C60"), Some((String::from("C60"), String::from("C60"))));
+        assert_eq!(eco_range("F10"), None);
+        assert_eq!(eco_range("B9-B99"), None);
+        assert_eq!(eco_range("1.e4 c5"), None);
+    }
+
+    #[test]
+    fn family_lines_keep_out_their_variations() {
+        let ruy_lopez = tags("C60");
+        assert!(ruy_lopez.contains(&String::from("Ruy_Lopez")));
+        assert!(!ruy_lopez.iter().any(|tags| tags.contains("Marshall")));
+        assert!(tags("B90-B99").iter().all(|tags| tags == "Sicilian_Defense Sicilian_Defense_Najdorf_Variation"));
+        assert_eq!(opening_tags("E99-E99"), None);
+    }
+
+    #[test]
+    fn move_prefixes() {
+        // Every line that goes through the moves
+        let sicilian = tags("1.e4 c5 2.Nf3 d6");
+        assert!(sicilian.contains(&String::from("Sicilian_Defense Sicilian_Defense_Modern_Variations")));
+        assert!(sicilian.contains(&String::from("Sicilian_Defense Sicilian_Defense_Najdorf_Variation")));
+        assert!(!sicilian.contains(&String::from("Sicilian_Defense")));
+        assert!(!sicilian.iter().any(|tags| tags.contains("Kan_Variation")));
+        assert_eq!(tags("1. e4 c5 2. Nf3 d6"), sicilian);
+
+        // Deeper than every line, so it's the longest one the moves go through
+        assert_eq!(tags("1.e4 e5 2.Nf3 Nc6 3.Bb5 a6 4.Bxc6 dxc6 5.O-O"), vec!["Ruy_Lopez Ruy_Lopez_Exchange_Variation"]);
+        assert_eq!(tags("1.h4"), Vec::<String>::new());
+        assert_eq!(opening_tags(""), None);
+    }
+
+    #[test]
+    fn lichess_tags_from_names() {
+        assert_eq!(lichess_tags("Ruy Lopez"), "Ruy_Lopez");
+        assert_eq!(lichess_tags("King's Gambit Accepted"), "Kings_Gambit_Accepted");
+        assert_eq!(lichess_tags("Sicilian Defense: Najdorf Variation, English Attack"),
+            "Sicilian_Defense Sicilian_Defense_Najdorf_Variation");
+        assert_eq!(lichess_tags("Sicilian Defense: Smith-Morra Gambit"), "Sicilian_Defense Sicilian_Defense_Smith-Morra_Gambit");
+        assert_eq!(lichess_tags("Queen's Pawn Game: Accelerated London System"),
+            "Queens_Pawn_Game Queens_Pawn_Game_Accelerated_London_System");
+    }
+}
@@ -49,16 +49,21 @@ static BUNDLE_FR: Lazy<FluentBundle<FluentResource, intl_memoizer::concurrent::I
 });
 
 pub fn tr(lang: &Language, key: &str) -> String {
+    try_tr(lang, key).unwrap_or_else(|| panic!("{}", ("Missing translation key ".to_owned() + key)))
+}
+
+// For keys that may not be in the translation files, like the opening tags from the puzzles
+pub fn try_tr(lang: &Language, key: &str) -> Option<String> {
     let bundle = match lang {
         Language::Portuguese => &BUNDLE_PTBR,
         Language::English => &BUNDLE_ENUS,
         Language::Spanish => &BUNDLE_ES,
         Language::French => &BUNDLE_FR,
     };
-    let msg = bundle.get_message(key).expect(&("Missing translation key ".to_owned() + key));
+    let msg = bundle.get_message(key)?;
     let mut errors = vec![];
     let pattern = msg.value().expect("Missing Value.");
-    bundle.format_pattern(&pattern, None, &mut errors).to_string()
+    Some(bundle.format_pattern(pattern, None, &mut errors).to_string())
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
@@ -91,11 +96,17 @@ pub struct PickListWrapper<D: DisplayTranslated> {
 
 pub trait DisplayTranslated {
     fn to_str_tr(&self) -> &str;
+
+    // Shown when there's no translation for the key
+    fn fallback(&self) -> String {
+        self.to_str_tr().replace('_', " ")
+    }
 }
 
 impl<D: DisplayTranslated> std::fmt::Display for PickListWrapper<D> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&tr(&self.lang, self.item.to_str_tr()))
+        let text = try_tr(&self.lang, self.item.to_str_tr()).unwrap_or_else(|| self.item.fallback());
+        f.write_str(&text)
     }
 }
 
@@ -7,8 +7,9 @@ use std::io::BufReader;
 use std::path::Path;
 use std::fs::File as StdFile;
 use std::str::FromStr;
+use std::time::Duration;
 use tokio::sync::mpsc::{self, Sender};
-use iced::widget::{Svg, Container, Button, row, Row, Column, Text, Radio, responsive};
+use iced::widget::{Svg, Container, Button, row, Row, Column, Text, TextInput, Radio, responsive};
 use iced::{Application, Element, Size, Subscription};
 use iced::{executor, alignment, Command, Alignment, Length, Settings };
 use iced::window;
@@ -21,9 +22,6 @@ use chess::{Board, BoardStatus, ChessMove, Color, Piece, Rank, Square, File, Gam
 use rodio::{Decoder, OutputStream, OutputStreamHandle};
 use rodio::source::{Source, Buffered};
 
-use rand::thread_rng;
-use rand::seq::SliceRandom;
-
 mod config;
 mod styles;
 mod search_tab;
@@ -35,14 +33,24 @@ use settings::{SettingsMessage, SettingsTab};
 mod puzzles;
 use puzzles::{PuzzleMessage, PuzzleTab, GameStatus};
 
+mod stats_tab;
+use stats_tab::{StatsMessage, StatsTab};
+
 mod eval;
 mod export;
 mod lang;
 mod openings;
+mod eco;
+mod pattern;
+mod rating;
+mod review;
+mod rush;
+use rush::{PuzzleRun, RunKind};
 
 pub mod models;
 pub mod schema;
 mod db;
+mod dump;
 
 #[macro_use]
 extern crate diesel;
@@ -64,6 +72,7 @@ pub enum TabId {
     Search,
     Settings,
     CurrentPuzzle,
+    Stats,
 }
 
 #[derive(Debug, Clone)]
@@ -73,6 +82,7 @@ pub enum Message {
     Search(SearchMesssage),
     Settings(SettingsMessage),
     PuzzleInfo(PuzzleMessage),
+    Stats(StatsMessage),
     SelectMode(config::GameMode),
     TabSelected(TabId),
     ShowHint,
@@ -82,7 +92,10 @@ pub enum Message {
     RedoPuzzle,
     ExportPDF(bool),
     LoadPuzzle(Option<Vec<config::Puzzle>>),
+    // The puzzles found by the search with this id
+    SearchFinished(usize, Option<Vec<config::Puzzle>>),
     ChangeSettings(Option<config::OfflinePuzzlesConfig>),
+    ProfileSelected(i32),
     EventOccurred(iced::Event),
     StartEngine,
     EngineStopped(bool),
@@ -90,6 +103,11 @@ pub enum Message {
     EngineReady(mpsc::Sender<String>),
     FavoritePuzzle,
     MinimizeUI,
+    RushTick,
+    StopRun,
+    FadeTick,
+    ChangeTypedMove(String),
+    SubmitTypedMove,
 }
 
 struct SoundPlayback {
@@ -154,10 +172,13 @@ struct OfflinePuzzles {
     search_tab: SearchTab,
     settings_tab: SettingsTab,
     puzzle_tab: PuzzleTab,
+    stats_tab: StatsTab,
     game_mode: config::GameMode,
+    run: Option<PuzzleRun>,
     sound_playback: Option<SoundPlayback>,
     lang: lang::Language,
     mini_ui: bool,
+    typed_move: String,
 }
 
 impl Default for OfflinePuzzles {
@@ -185,12 +206,15 @@ impl Default for OfflinePuzzles {
             search_tab: SearchTab::new(),
             settings_tab: SettingsTab::new(),
             puzzle_tab: PuzzleTab::new(),
+            stats_tab: StatsTab::new(),
             active_tab: TabId::Search,
 
             game_mode: config::GameMode::Puzzle,
+            run: None,
             sound_playback: SoundPlayback::init_sound(),
             lang: config::SETTINGS.lang,
             mini_ui: false,
+            typed_move: String::new(),
         }
     }
 }
@@ -237,6 +261,132 @@ fn get_notation_string(board: Board, promo_piece: Piece, from: Square, to: Squar
     move_made_notation
 }
 
+impl OfflinePuzzles {
+    // A wrong move in a puzzle rush skips to the next puzzle, unless it was the last strike
+    // (and in a streak the first one is the last).
+    fn run_strike(&mut self) -> Command<Message> {
+        let run_over = self.run.as_mut().map_or(true, PuzzleRun::add_strike);
+        let has_more_puzzles = self.puzzle_tab.current_puzzle < self.puzzle_tab.puzzles.len() - 1;
+        if run_over || !has_more_puzzles {
+            self.end_run();
+            self.show_session_review();
+            Command::none()
+        } else {
+            self.update(Message::ShowNextPuzzle)
+        }
+    }
+
+    fn end_run(&mut self) {
+        if let Some(run) = &mut self.run {
+            run.finish();
+        }
+        self.puzzle_tab.abandon_attempt();
+        self.game_mode = config::GameMode::Puzzle;
+        self.puzzle_tab.game_status = GameStatus::PuzzleEnded;
+        self.from_square = None;
+        self.hint_square = None;
+        self.puzzle_status = lang::tr(&self.lang, "rush_over");
+    }
+
+    // At the end of a set of puzzles we go over how each of them went
+    fn show_session_review(&mut self) {
+        self.puzzle_tab.show_session = true;
+        self.active_tab = TabId::CurrentPuzzle;
+    }
+
+    // Starts solving the puzzles, from a search (which brings its level window
+    // and the kind of run) or from anywhere else.
+    fn load_puzzles(&mut self, puzzles_vec: Option<Vec<config::Puzzle>>, level_window: Option<i32>, run: Option<RunKind>) {
+        self.from_square = None;
+        self.puzzle_tab.abandon_attempt();
+        self.puzzle_tab.session.clear();
+        self.puzzle_tab.show_session = false;
+        self.puzzle_tab.level_window = level_window;
+        self.run = None;
+        self.game_mode = config::GameMode::Puzzle;
+        if self.engine_state != EngineStatus::TurnedOff {
+            if let Some(sender) = &self.engine_sender {
+                sender.blocking_send(String::from(eval::STOP_COMMAND)).expect("Error stopping engine.");
+            }
+        }
+        if let Some(puzzles_vec) = puzzles_vec {
+            if !puzzles_vec.is_empty() {
+                self.puzzle_tab.puzzles = puzzles_vec;
+                self.puzzle_tab.current_puzzle_move = 1;
+                self.puzzle_tab.current_puzzle = 0;
+                self.puzzle_tab.recenter(0);
+
+                self.board = Board::from_str(&self.puzzle_tab.puzzles[0].fen).unwrap();
+                let puzzle_moves: Vec<&str> = self.puzzle_tab.puzzles[0].moves.split_whitespace().collect();
+
+                // The last opponent's move is in the "moves" field of the cvs,
+                // so we need to apply it.
+                let movement = ChessMove::new(
+                        Square::from_str(&puzzle_moves[0][..2]).unwrap(),
+                        Square::from_str(&puzzle_moves[0][2..4]).unwrap(), PuzzleTab::check_promotion(puzzle_moves[0]));
+
+                self.last_move_from = Some(movement.get_source());
+                self.last_move_to = Some(movement.get_dest());
+
+                self.board = self.board.make_move_new(movement);
+                self.analysis_history = vec![self.board];
+
+                if self.board.side_to_move() == Color::White {
+                    self.puzzle_status = lang::tr(&self.lang, "white_to_move");
+                } else {
+                    self.puzzle_status = lang::tr(&self.lang, "black_to_move");
+                }
+                self.puzzle_tab.current_puzzle_fen = san_correct_ep(self.board.to_string());
+                self.puzzle_tab.current_puzzle_side = self.board.side_to_move();
+                self.puzzle_tab.game_status = GameStatus::Playing;
+                self.puzzle_tab.start_attempt();
+                if let Some(kind) = run {
+                    self.run = Some(PuzzleRun::new(kind));
+                    self.game_mode = kind.game_mode();
+                }
+            } else {
+                // Just putting the default position to make it obvious the search ended.
+                self.board = Board::default();
+                self.last_move_from = None;
+                self.last_move_to = None;
+                self.puzzle_tab.game_status = GameStatus::NoPuzzles;
+                self.puzzle_status = lang::tr(&self.lang, "no_puzzle_found");
+            }
+        } else {
+            self.board = Board::default();
+            self.last_move_from = None;
+            self.last_move_to = None;
+            self.puzzle_tab.game_status = GameStatus::NoPuzzles;
+            self.puzzle_status = lang::tr(&self.lang, "no_puzzle_found");
+        }
+    }
+
+    // The display modes only apply to the puzzle being solved, the analysis board is always shown as is
+    fn board_display(&self) -> config::BoardDisplay {
+        if self.game_mode == config::GameMode::Analysis {
+            config::BoardDisplay::Normal
+        } else {
+            self.settings_tab.board_display
+        }
+    }
+
+    // In the fading display the pieces disappear some seconds after the puzzle is shown,
+    // in both hidden modes they're back once it ends.
+    fn pieces_hidden(&self) -> bool {
+        if self.puzzle_tab.game_status != GameStatus::Playing {
+            return false;
+        }
+        match self.board_display() {
+            config::BoardDisplay::Blindfold => true,
+            config::BoardDisplay::Fading => {
+                let fade = Duration::from_secs(self.settings_tab.fade_seconds.parse().unwrap_or(0));
+                self.puzzle_tab.time_on_puzzle().is_some_and(|time| time >= fade)
+            }
+            config::BoardDisplay::Normal | config::BoardDisplay::Monochrome => false,
+        }
+    }
+}
+
 impl Application for OfflinePuzzles {
     type Executor = executor::Default;
     type Theme = styles::Theme;
@@ -244,9 +394,26 @@ impl Application for OfflinePuzzles {
     type Flags = ();
 
     fn new(_flags: ()) -> (OfflinePuzzles, Command<Message>) {
-        (
-            Self::default(),
+        let mut commands = vec![
             iced::font::load(Cow::from(config::CHESS_ALPHA_BYTES)).map(Message::ChessFontLoaded),
+        ];
+        // The profile used last time, unless it's not in the database anymore
+        let profiles = db::profiles();
+        if profiles.iter().any(|(id, _)| *id == config::SETTINGS.active_profile) {
+            db::set_active_profile(config::SETTINGS.active_profile);
+        }
+        let mut app = Self::default();
+        // With more than one profile we start at the settings, where the profile is picked
+        if profiles.len() > 1 {
+            app.active_tab = TabId::Settings;
+        }
+        // Start with the failed puzzles that are due for review, if there are any
+        if db::count_due_reviews() > 0 {
+            commands.push(Command::perform(SearchTab::load_reviews(), Message::LoadPuzzle));
+        }
+        (
+            app,
+            Command::batch(commands)
         )
     }
 
@@ -260,12 +427,12 @@ impl Application for OfflinePuzzles {
                 let side =
                     match self.game_mode {
                         config::GameMode::Analysis => { self.analysis.side_to_move() }
-                        config::GameMode::Puzzle => { self.board.side_to_move() }
+                        config::GameMode::Puzzle | config::GameMode::Rush | config::GameMode::Streak => { self.board.side_to_move() }
                     };
                 let color =
                     match self.game_mode {
                         config::GameMode::Analysis => { self.analysis.current_position().color_on(pos) }
-                        config::GameMode::Puzzle => { self.board.color_on(pos) }
+                        config::GameMode::Puzzle | config::GameMode::Rush | config::GameMode::Streak => { self.board.color_on(pos) }
                     };
 
                 if (self.puzzle_tab.is_playing() || self.game_mode == config::GameMode::Analysis) && color == Some(side) {
@@ -277,12 +444,12 @@ impl Application for OfflinePuzzles {
                 let side =
                     match self.game_mode {
                         config::GameMode::Analysis => { self.analysis.side_to_move() }
-                        config::GameMode::Puzzle => { self.board.side_to_move() }
+                        config::GameMode::Puzzle | config::GameMode::Rush | config::GameMode::Streak => { self.board.side_to_move() }
                     };
                 let color =
                     match self.game_mode {
                         config::GameMode::Analysis => { self.analysis.current_position().color_on(to) }
-                        config::GameMode::Puzzle => { self.board.color_on(to) }
+                        config::GameMode::Puzzle | config::GameMode::Rush | config::GameMode::Streak => { self.board.color_on(to) }
                     };
                 // If the user clicked on another piece of his own side,
                 // just replace the previous selection and exit
@@ -339,13 +506,20 @@ impl Application for OfflinePuzzles {
                         self.puzzle_tab.current_puzzle_move += 1;
 
                         if self.puzzle_tab.current_puzzle_move == correct_moves.len() {
+                            self.puzzle_tab.finish_attempt();
+                            let in_run = self.game_mode.is_run();
+                            if in_run {
+                                if let Some(run) = &mut self.run {
+                                    run.score += 1;
+                                }
+                            }
                             if self.settings_tab.saved_configs.play_sound {
                                 if let Some(audio) = &self.sound_playback {
                                     audio.play_audio(SoundPlayback::ONE_PIECE_SOUND);
                                 }
                             }
                             if self.puzzle_tab.current_puzzle < self.puzzle_tab.puzzles.len() - 1 {
-                                if self.settings_tab.saved_configs.auto_load_next {
+                                if self.settings_tab.saved_configs.auto_load_next || in_run {
                                     // The previous puzzle ended, and we still have puzzles available,
                                     // so we prepare the next one.
                                     self.puzzle_tab.current_puzzle += 1;
@@ -375,6 +549,7 @@ impl Application for OfflinePuzzles {
 
                                     self.puzzle_tab.current_puzzle_side = self.board.side_to_move();
                                     self.puzzle_tab.current_puzzle_fen = san_correct_ep(self.board.to_string());
+                                    self.puzzle_tab.start_attempt();
                                 } else {
                                     self.puzzle_tab.game_status = GameStatus::PuzzleEnded;
                                     self.puzzle_status = lang::tr(&self.lang, "correct_puzzle");
@@ -393,6 +568,10 @@ impl Application for OfflinePuzzles {
                                 self.last_move_from = None;
                                 self.last_move_to = None;
                                 self.puzzle_status = lang::tr(&self.lang, "all_puzzles_done");
+                                if in_run {
+                                    self.end_run();
+                                }
+                                self.show_session_review();
                             }
                         } else {
                             if self.settings_tab.saved_configs.play_sound {
@@ -414,6 +593,10 @@ impl Application for OfflinePuzzles {
                             self.puzzle_status = lang::tr(&self.lang, "correct_move");
                         }
                     } else {
+                        self.puzzle_tab.add_wrong_move();
+                        if self.game_mode.is_run() {
+                            return self.run_strike();
+                        }
                         #[allow(clippy::collapsible_else_if)]
                         if self.board.side_to_move() == Color::White {
                             self.puzzle_status = lang::tr(&self.lang, "wrong_move_white_play");
@@ -428,7 +611,14 @@ impl Application for OfflinePuzzles {
                 Command::none()
             } (_, Message::TabSelected(selected)) => {
                 self.active_tab = selected;
-                Command::none()
+                // The attempts may have changed since the last time it was shown
+                if selected == TabId::Stats {
+                    self.stats_tab.update(StatsMessage::Refresh)
+                } else {
+                    Command::none()
+                }
+            } (_, Message::Stats(message)) => {
+                self.stats_tab.update(message)
             } (_, Message::Settings(message)) => {
                 self.settings_tab.update(message)
             } (_, Message::SelectMode(message)) => {
@@ -448,12 +638,15 @@ impl Application for OfflinePuzzles {
                 let moves = self.puzzle_tab.puzzles[self.puzzle_tab.current_puzzle].moves.split_whitespace().collect::<Vec<&str>>();
                 if !moves.is_empty() && moves.len() > self.puzzle_tab.current_puzzle_move {
                     self.hint_square = Some(Square::from_str(&moves[self.puzzle_tab.current_puzzle_move][..2]).unwrap());
+                    self.puzzle_tab.add_hint();
                 } else {
                     self.hint_square = None;
                 }
 
                 Command::none()
             } (_, Message::ShowNextPuzzle) => {
+                // Closed before moving on, so the next puzzle follows the new rating
+                self.puzzle_tab.abandon_attempt();
                 // The previous puzzle ended, and we still have puzzles available,
                 // so we prepare the next one.
                 self.puzzle_tab.current_puzzle += 1;
@@ -484,10 +677,14 @@ impl Application for OfflinePuzzles {
                 self.puzzle_tab.current_puzzle_fen = san_correct_ep(self.board.to_string());
                 self.puzzle_tab.current_puzzle_side = self.board.side_to_move();
                 self.puzzle_tab.game_status = GameStatus::Playing;
-                self.game_mode = config::GameMode::Puzzle;
+                self.puzzle_tab.start_attempt();
+                if self.game_mode == config::GameMode::Analysis {
+                    self.game_mode = config::GameMode::Puzzle;
+                }
                 Command::none()
             } (_, Message::ShowPreviousPuzzle) => {
                 if self.puzzle_tab.current_puzzle > 0 && self.game_mode == config::GameMode::Puzzle {
+                    self.puzzle_tab.abandon_attempt();
                     self.puzzle_tab.current_puzzle -= 1;
                     self.puzzle_tab.current_puzzle_move = 1;
 
@@ -516,6 +713,7 @@ impl Application for OfflinePuzzles {
                     self.puzzle_tab.current_puzzle_fen = san_correct_ep(self.board.to_string());
                     self.puzzle_tab.current_puzzle_side = self.board.side_to_move();
                     self.puzzle_tab.game_status = GameStatus::Playing;
+                    self.puzzle_tab.start_attempt();
                 }
                 Command::none()
             } (_, Message::GoBackMove) => {
@@ -530,6 +728,7 @@ impl Application for OfflinePuzzles {
                 }
                 Command::none()
             } (_, Message::RedoPuzzle) => {
+                self.puzzle_tab.abandon_attempt();
                 self.puzzle_tab.current_puzzle_move = 1;
 
                 let puzzle_moves: Vec<&str> = self.puzzle_tab.puzzles[self.puzzle_tab.current_puzzle].moves.split_whitespace().collect();
@@ -555,60 +754,19 @@ impl Application for OfflinePuzzles {
                 }
                 self.puzzle_tab.current_puzzle_side = self.board.side_to_move();
                 self.puzzle_tab.game_status = GameStatus::Playing;
+                self.puzzle_tab.start_attempt();
                 Command::none()
             } (_, Message::LoadPuzzle(puzzles_vec)) => {
-                self.from_square = None;
-                self.search_tab.show_searching_msg = false;
-                self.game_mode = config::GameMode::Puzzle;
-                if self.engine_state != EngineStatus::TurnedOff {
-                    if let Some(sender) = &self.engine_sender {
-                        sender.blocking_send(String::from(eval::STOP_COMMAND)).expect("Error stopping engine.");
-                    }
-                }
-                if let Some(puzzles_vec) = puzzles_vec {
-                    if !puzzles_vec.is_empty() {
-                        self.puzzle_tab.puzzles = puzzles_vec;
-                        self.puzzle_tab.puzzles.shuffle(&mut thread_rng());
-                        self.puzzle_tab.current_puzzle_move = 1;
-                        self.puzzle_tab.current_puzzle = 0;
-
-                        self.board = Board::from_str(&self.puzzle_tab.puzzles[0].fen).unwrap();
-                        let puzzle_moves: Vec<&str> = self.puzzle_tab.puzzles[0].moves.split_whitespace().collect();
-
-                        // The last opponent's move is in the "moves" field of the cvs,
-                        // so we need to apply it.
-                        let movement = ChessMove::new(
-                                Square::from_str(&puzzle_moves[0][..2]).unwrap(),
-                                Square::from_str(&puzzle_moves[0][2..4]).unwrap(), PuzzleTab::check_promotion(puzzle_moves[0]));
-
-                        self.last_move_from = Some(movement.get_source());
-                        self.last_move_to = Some(movement.get_dest());
-
-                        self.board = self.board.make_move_new(movement);
-                        self.analysis_history = vec![self.board];
-
-                        if self.board.side_to_move() == Color::White {
-                            self.puzzle_status = lang::tr(&self.lang, "white_to_move");
-                        } else {
-                            self.puzzle_status = lang::tr(&self.lang, "black_to_move");
-                        }
-                        self.puzzle_tab.current_puzzle_fen = san_correct_ep(self.board.to_string());
-                        self.puzzle_tab.current_puzzle_side = self.board.side_to_move();
-                        self.puzzle_tab.game_status = GameStatus::Playing;
-                    } else {
-                        // Just putting the default position to make it obvious the search ended.
-                        self.board = Board::default();
-                        self.last_move_from = None;
-                        self.last_move_to = None;
-                        self.puzzle_tab.game_status = GameStatus::NoPuzzles;
-                        self.puzzle_status = lang::tr(&self.lang, "no_puzzle_found");
+                self.load_puzzles(puzzles_vec, None, None);
+                Command::none()
+            } (_, Message::SearchFinished(job_id, puzzles_vec)) => {
+                // A search that was already replaced is ignored
+                match self.search_tab.search_job.take() {
+                    Some(job) if job.id() == job_id => {
+                        self.search_tab.show_searching_msg = false;
+                        self.load_puzzles(puzzles_vec, job.level_window(), job.run);
                     }
-                } else {
-                    self.board = Board::default();
-                    self.last_move_from = None;
-                    self.last_move_to = None;
-                    self.puzzle_tab.game_status = GameStatus::NoPuzzles;
-                    self.puzzle_status = lang::tr(&self.lang, "no_puzzle_found");
+                    job => self.search_tab.search_job = job,
                 }
                 Command::none()
             } (_, Message::ChangeSettings(message)) => {
@@ -620,11 +778,48 @@ impl Application for OfflinePuzzles {
                     self.search_tab.theme.lang = self.lang;
                     self.search_tab.opening.lang = self.lang;
                     self.puzzle_tab.lang = self.lang;
+                    self.stats_tab.lang = self.lang;
                     self.settings_tab.saved_configs = settings;
                 }
                 Command::none()
+            } (_, Message::ProfileSelected(profile_id)) => {
+                // Whatever is in progress still counts for the profile that started it
+                if self.game_mode.is_run() {
+                    self.end_run();
+                } else {
+                    self.puzzle_tab.abandon_attempt();
+                }
+                db::set_active_profile(profile_id);
+                SettingsTab::save_active_profile(profile_id);
+                self.settings_tab.active_profile = profile_id;
+                self.puzzle_tab.user_rating = db::current_rating();
+                self.puzzle_tab.rating_change = None;
+                self.search_tab.load_presets();
+                self.stats_tab.update(StatsMessage::Refresh)
             }
-             (_, Message::PuzzleInfo(message)) => {
+             (_, Message::PuzzleInfo(PuzzleMessage::ReplayPuzzle(puzzle_id))) => {
+                if let Some(index) = self.puzzle_tab.puzzle_index(&puzzle_id) {
+                    if self.game_mode.is_run() {
+                        self.end_run();
+                    }
+                    self.from_square = None;
+                    self.hint_square = None;
+                    self.game_mode = config::GameMode::Puzzle;
+                    self.puzzle_tab.abandon_attempt();
+                    self.puzzle_tab.current_puzzle = index;
+                    return self.update(Message::RedoPuzzle);
+                }
+                Command::none()
+            } (_, Message::PuzzleInfo(PuzzleMessage::AnalyzePuzzle(puzzle_id))) => {
+                if self.puzzle_tab.puzzle_index(&puzzle_id).is_none() {
+                    return Command::none();
+                }
+                // The analysis starts from the position the puzzle is shown in,
+                // and looking at it isn't another attempt.
+                let command = self.update(Message::PuzzleInfo(PuzzleMessage::ReplayPuzzle(puzzle_id)));
+                self.puzzle_tab.attempt = None;
+                Command::batch([command, self.update(Message::SelectMode(config::GameMode::Analysis))])
+            } (_, Message::PuzzleInfo(message)) => {
                 self.puzzle_tab.update(message)
             } (_, Message::Search(message)) => {
                 self.search_tab.update(message)
@@ -719,6 +914,58 @@ impl Application for OfflinePuzzles {
             } (_, Message::FavoritePuzzle) => {
                 db::toggle_favorite(self.puzzle_tab.puzzles[self.puzzle_tab.current_puzzle].clone());
                 Command::none()
+            } (_, Message::RushTick) => {
+                if self.game_mode == config::GameMode::Rush && self.run.as_ref().is_some_and(PuzzleRun::time_is_up) {
+                    self.end_run();
+                    self.show_session_review();
+                }
+                Command::none()
+            } (_, Message::StopRun) => {
+                if self.game_mode.is_run() {
+                    self.end_run();
+                    self.show_session_review();
+                }
+                Command::none()
+            } (_, Message::FadeTick) => {
+                // Nothing to do, the view checks if it's time to hide the pieces
+                Command::none()
+            } (_, Message::ChangeTypedMove(value)) => {
+                self.typed_move = value;
+                Command::none()
+            } (_, Message::SubmitTypedMove) => {
+                let board = if self.game_mode == config::GameMode::Analysis {
+                    self.analysis.current_position()
+                } else {
+                    self.board
+                };
+                let typed = self.typed_move.trim().to_string();
+                // SAN (e.g. "Nxf7+") or the squares (e.g. "g5f7")
+                let movement = ChessMove::from_san(&board, &typed).ok().or_else(|| {
+                    let coords = typed.to_lowercase();
+                    match (coords.get(..2).map(Square::from_str), coords.get(2..4).map(Square::from_str)) {
+                        (Some(Ok(from)), Some(Ok(to))) => Some(ChessMove::new(from, to, PuzzleTab::check_promotion(&coords))),
+                        _ => None,
+                    }
+                }).filter(|movement| board.legal(*movement));
+
+                if let Some(movement) = movement {
+                    self.typed_move.clear();
+                    // Played as if the squares were clicked, with the typed promotion
+                    let promotion = self.search_tab.piece_to_promote_to;
+                    if let Some(piece) = movement.get_promotion() {
+                        self.search_tab.piece_to_promote_to = piece;
+                    }
+                    self.from_square = None;
+                    let mut command = self.update(Message::SelectSquare(movement.get_source()));
+                    if self.from_square == Some(movement.get_source()) {
+                        command = self.update(Message::SelectSquare(movement.get_dest()));
+                    }
+                    self.search_tab.piece_to_promote_to = promotion;
+                    command
+                } else {
+                    self.puzzle_status = lang::tr(&self.lang, "invalid_typed_move");
+                    Command::none()
+                }
             } (_, Message::ChessFontLoaded(_)) => {
                 Command::none()
             } (_, Message::MinimizeUI) => {
@@ -741,16 +988,26 @@ impl Application for OfflinePuzzles {
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        match self.engine_state {
-            EngineStatus::TurnedOff => {
-                iced::subscription::events().map(Message::EventOccurred)
-            } _ => {
-                Subscription::batch(vec![
-                    Engine::run_engine(self.engine.clone()),
-                    iced::subscription::events().map(Message::EventOccurred)
-                ])
-            }
+        let mut subscriptions = vec![iced::subscription::events().map(Message::EventOccurred)];
+        if self.engine_state != EngineStatus::TurnedOff {
+            subscriptions.push(Engine::run_engine(self.engine.clone()));
+        }
+        if self.search_tab.openings_progress.is_some() {
+            subscriptions.push(SearchTab::load_openings());
+        }
+        if let Some(search_job) = &self.search_tab.search_job {
+            subscriptions.push(SearchTab::run_search(search_job.clone()));
         }
+        if let Some(puzzle_db_job) = &self.settings_tab.puzzle_db_job {
+            subscriptions.push(SettingsTab::run_puzzle_db_job(puzzle_db_job.clone()));
+        }
+        if self.game_mode == config::GameMode::Rush {
+            subscriptions.push(iced::time::every(Duration::from_secs(1)).map(|_| Message::RushTick));
+        }
+        if self.board_display() == config::BoardDisplay::Fading && self.puzzle_tab.game_status == GameStatus::Playing && !self.pieces_hidden() {
+            subscriptions.push(iced::time::every(Duration::from_millis(250)).map(|_| Message::FadeTick));
+        }
+        Subscription::batch(subscriptions)
     }
 
     fn view(&self) -> Element<Message, iced::Renderer<styles::Theme>> {
@@ -761,6 +1018,20 @@ impl Application for OfflinePuzzles {
         } else {
             db::is_favorite(&self.puzzle_tab.puzzles[self.puzzle_tab.current_puzzle].puzzle_id)
         };
+        let board_display = self.board_display();
+        let hide_pieces = self.pieces_hidden();
+        // With the pieces hidden the position is given as text
+        let position_text = if hide_pieces {
+            Some(lang::tr(&self.lang, "position_fen") + &san_correct_ep(self.board.to_string()) + "\n" +
+                &lang::tr(&self.lang, "moves_played") + &self.puzzle_tab.moves_played(&self.lang))
+        } else {
+            None
+        };
+        let typed_move = if board_display == config::BoardDisplay::Normal {
+            None
+        } else {
+            Some(self.typed_move.as_str())
+        };
         let resp = responsive(move |size| {
             gen_view(
                 self.game_mode,
@@ -769,12 +1040,20 @@ impl Application for OfflinePuzzles {
                 self.settings_tab.show_coordinates,
                 &self.board,
                 &self.analysis.current_position(),
+                hide_pieces,
+                board_display == config::BoardDisplay::Monochrome,
+                position_text.clone(),
+                typed_move,
                 self.from_square,
                 self.last_move_from,
                 self.last_move_to,
                 self.hint_square,
                 self.settings_tab.saved_configs.piece_theme,
                 &self.puzzle_status,
+                match &self.run {
+                    Some(run) => run.status(&self.lang),
+                    None => self.puzzle_tab.rating_status(),
+                },
                 is_fav,
                 has_more_puzzles,
                 has_previous,
@@ -789,9 +1068,11 @@ impl Application for OfflinePuzzles {
                 self.search_tab.tab_label(),
                 self.settings_tab.tab_label(),
                 self.puzzle_tab.tab_label(),
+                self.stats_tab.tab_label(),
                 self.search_tab.view(),
                 self.settings_tab.view(),
                 self.puzzle_tab.view(),
+                self.stats_tab.view(),
                 &self.lang,
                 size,
                 self.mini_ui,
@@ -813,12 +1094,17 @@ fn gen_view<'a>(
     show_coordinates: bool,
     board: &Board,
     analysis: &Board,
+    hide_pieces: bool,
+    monochrome: bool,
+    position_text: Option<String>,
+    typed_move: Option<&'a str>,
     from_square: Option<Square>,
     last_move_from: Option<Square>,
     last_move_to: Option<Square>,
     hint_square: Option<Square>,
     piece_theme: styles::PieceTheme,
     puzzle_status: &'a str,
+    side_status: String,
     is_fav: bool,
     has_more_puzzles: bool,
     has_previous: bool,
@@ -833,9 +1119,11 @@ fn gen_view<'a>(
     search_tab_label: TabLabel,
     settings_tab_label: TabLabel,
     puzzle_tab_label: TabLabel,
+    stats_tab_label: TabLabel,
     search_tab: Element<'a, Message, iced::Renderer<styles::Theme>>,
     settings_tab: Element<'a, Message, iced::Renderer<styles::Theme>>,
     puzzle_tab: Element<'a, Message, iced::Renderer<styles::Theme>>,
+    stats_tab: Element<'a, Message, iced::Renderer<styles::Theme>>,
     lang: &lang::Language,
     size: Size,
     mini_ui: bool,
@@ -847,18 +1135,26 @@ fn gen_view<'a>(
 
     let is_white = (current_puzzle_side == Color::White) ^ flip_board;
 
+    // Space below the board for the position text and the move input
+    let mut extra_height = 0.;
+    if position_text.is_some() {
+        extra_height += 45.;
+    }
+    if typed_move.is_some() {
+        extra_height += 40.;
+    }
     //Reserve more space below the board if we'll show the engine eval
     let board_height = if engine_eval.is_empty() {
         if show_coordinates {
-            ((size.height - 120.) / 8.) as u16
+            ((size.height - 120. - extra_height) / 8.) as u16
         } else {
-            ((size.height - 110.) / 8.) as u16
+            ((size.height - 110. - extra_height) / 8.) as u16
         }
     } else {
         if show_coordinates {
-            ((size.height - 150.) / 8.) as u16
+            ((size.height - 150. - extra_height) / 8.) as u16
         } else {
-            ((size.height - 140.) / 8.) as u16
+            ((size.height - 140. - extra_height) / 8.) as u16
         }
     };
 
@@ -880,17 +1176,24 @@ fn gen_view<'a>(
                     config::GameMode::Analysis => {
                         (analysis.piece_on(pos),
                         analysis.color_on(pos))
-                    } config::GameMode::Puzzle => {
+                    } config::GameMode::Puzzle | config::GameMode::Rush | config::GameMode::Streak => {
                         (board.piece_on(pos),
                         board.color_on(pos))
                     }
                 };
+            let (piece, color) = if hide_pieces {
+                (None, None)
+            } else if monochrome {
+                (piece, color.map(|_| Color::White))
+            } else {
+                (piece, color)
+            };
 
             let mut text;
             let light_square = (rank + file) % 2 != 0;
 
             let selected =
-                if game_mode == config::GameMode::Puzzle {
+                if game_mode != config::GameMode::Analysis {
                     from_square == Some(pos)    ||
                     last_move_from == Some(pos) ||
                     last_move_to == Some(pos)   ||
@@ -1066,6 +1369,8 @@ fn gen_view<'a>(
         } else {
             navigation_row = navigation_row.push(Button::new(Text::new(lang::tr(lang, "start_engine"))).on_press(Message::StartEngine));
         }
+    } else if game_mode.is_run() {
+        navigation_row = navigation_row.push(Button::new(Text::new(lang::tr(lang, "stop_rush"))).on_press(Message::StopRun));
     } else {
         if has_previous {
             navigation_row = navigation_row.push(Button::new(Text::new(lang::tr(lang, "previous"))).on_press(Message::ShowPreviousPuzzle))
@@ -1095,7 +1400,27 @@ fn gen_view<'a>(
         }
     }
 
-    board_col = board_col.push(Text::new(puzzle_status)).push(game_mode_row).push(navigation_row);
+    let status_row = row![
+        Text::new(puzzle_status),
+        Text::new(side_status),
+    ].spacing(15);
+    if let Some(position_text) = position_text {
+        board_col = board_col.push(Text::new(position_text).size(15));
+    }
+    if let Some(typed_move) = typed_move {
+        board_col = board_col.push(row![
+            TextInput::new(&lang::tr(lang, "type_move"), typed_move)
+                .on_input(Message::ChangeTypedMove)
+                .on_submit(Message::SubmitTypedMove)
+                .width(150).padding(5),
+            Button::new(Text::new(lang::tr(lang, "play_move"))).on_press(Message::SubmitTypedMove),
+        ].spacing(10).padding(3).align_items(Alignment::Center));
+    }
+    board_col = board_col.push(status_row);
+    if !game_mode.is_run() {
+        board_col = board_col.push(game_mode_row);
+    }
+    board_col = board_col.push(navigation_row);
     if !engine_eval.is_empty() {
         board_col = board_col.push(
             row![
@@ -1113,6 +1438,7 @@ fn gen_view<'a>(
                 .push(TabId::Search, search_tab_label, search_tab)
                 .push(TabId::Settings, settings_tab_label, settings_tab)
                 .push(TabId::CurrentPuzzle ,puzzle_tab_label, puzzle_tab)
+                .push(TabId::Stats, stats_tab_label, stats_tab)
                 .tab_bar_position(iced_aw::TabBarPosition::Top)
                 .set_active_tab(active_tab);
 
@@ -1146,6 +1472,7 @@ trait Tab {
 }
 
 fn main() -> iced::Result {
+    db::run_migrations();
     OfflinePuzzles::run(Settings {
         window: iced::window::Settings {
             size: (
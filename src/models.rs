@@ -1,5 +1,5 @@
 use diesel::prelude::*;
-use crate::schema::favs;
+use crate::schema::{attempts, favs, profiles, puzzle_openings, puzzle_source, puzzle_themes, puzzles, ratings, reviews, rush_runs, saved_searches, streaks};
 
 /*
 #[derive(Queryable)]
@@ -19,6 +19,7 @@ pub struct Favorite {
 #[derive(Insertable)]
 #[diesel(table_name = favs)]
 pub struct NewFavorite<'a> {
+    pub profile_id: i32,
     pub puzzle_id: &'a str,
     pub fen: &'a str,
     pub moves: &'a str,
@@ -30,3 +31,132 @@ pub struct NewFavorite<'a> {
     pub game_url: &'a str,
     pub opening_tags: &'a str,
 }
+
+#[derive(Insertable)]
+#[diesel(table_name = attempts)]
+pub struct NewAttempt<'a> {
+    pub puzzle_id: &'a str,
+    pub attempted_at: i64,
+    pub solved: bool,
+    pub wrong_moves: i32,
+    pub hints: i32,
+    pub time_taken_ms: i64,
+    pub puzzle_rating: i32,
+    pub themes: &'a str,
+    pub opening_tags: &'a str,
+    pub profile_id: i32,
+}
+
+#[derive(Queryable)]
+pub struct AttemptRecord {
+    pub id: i32,
+    pub puzzle_id: String,
+    pub attempted_at: i64,
+    pub solved: bool,
+    pub wrong_moves: i32,
+    pub hints: i32,
+    pub time_taken_ms: i64,
+    pub puzzle_rating: i32,
+    pub themes: String,
+    pub opening_tags: String,
+    pub profile_id: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = puzzles)]
+pub struct NewPuzzle<'a> {
+    pub puzzle_id: &'a str,
+    pub fen: &'a str,
+    pub moves: &'a str,
+    pub rating: i32,
+    pub rd: i32,
+    pub popularity: i32,
+    pub nb_plays: i32,
+    pub themes: &'a str,
+    pub game_url: &'a str,
+    pub opening_tags: &'a str,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = ratings)]
+pub struct NewRating<'a> {
+    pub rated_at: i64,
+    pub puzzle_id: &'a str,
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+    pub profile_id: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = reviews)]
+pub struct NewReview<'a> {
+    pub profile_id: i32,
+    pub puzzle_id: &'a str,
+    pub fen: &'a str,
+    pub moves: &'a str,
+    pub rating: i32,
+    pub rd: i32,
+    pub popularity: i32,
+    pub nb_plays: i32,
+    pub themes: &'a str,
+    pub game_url: &'a str,
+    pub opening_tags: &'a str,
+    pub ease: f64,
+    pub interval_days: i32,
+    pub repetitions: i32,
+    pub due_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = rush_runs)]
+pub struct NewRushRun {
+    pub finished_at: i64,
+    pub minutes: i32,
+    pub score: i32,
+    pub profile_id: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = streaks)]
+pub struct NewStreak {
+    pub finished_at: i64,
+    pub length: i32,
+    pub profile_id: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = profiles)]
+pub struct NewProfile<'a> {
+    pub name: &'a str,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = saved_searches)]
+pub struct NewSavedSearch<'a> {
+    pub profile_id: i32,
+    pub name: &'a str,
+    pub preset: &'a str,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = puzzle_themes)]
+pub struct NewPuzzleTheme<'a> {
+    pub puzzle_id: &'a str,
+    pub theme: &'a str,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = puzzle_openings)]
+pub struct NewPuzzleOpening<'a> {
+    pub puzzle_id: &'a str,
+    pub opening_tag: &'a str,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = puzzle_source)]
+pub struct NewPuzzleSource<'a> {
+    pub id: i32,
+    pub csv_path: &'a str,
+    pub modified_at: i64,
+}
@@ -1,77 +1,84 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
 
 use crate::lang::{self, PickListWrapper, DisplayTranslated};
+use crate::{config, db};
+use crate::dump::PuzzleDump;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
-pub enum Openings {
-    Any, Sicilian, French, QueensPawnGame, ItalianGame, CaroKann,Scandinavian,QueensGambitDeclined,
-    English, RuyLopez, IndianDefense, ScotchGame, RussianGame, PhilidorDefense, ModernDefense,
-    FourKnightsGame, KingsGambitAccepted, ZukertortOpening, BishopsOpening, SlavDefense, PircDefense,
-    KingsPawnGame, ViennaGame, QueensGambitAccepted, KingsIndianDefense, Benoni, NimzowitschDefense,
-    AlekhineDefense, NimzoLarsenAttack, HorwitzDefense, KingsGambitDeclined, OwenDefense, Bird,
-    Dutch, NimzoIndianDefense, VantKruijsOpening, SemiSlav, CenterGame, HungarianOpening, ThreeKnightsOpening,
-    PonzianiOpening, EnglundGambit, GrunfeldDefense, BlackmarDiemerGambit, ElephantGambit, PolishOpening,
-    DanishGambit, KingsIndianAttack, TrompowskyAttack, EnglishDefense, GrobOpening, RapportJobavaSystem,
-    TarraschDefense, CatalanOpening, Reti, QueensIndianDefense, LondonSystem
+// Where the catalog is cached, so we don't need to read the puzzles on every start
+const CATALOG_CACHE: &str = "openings.json";
+const DB_SOURCE: &str = "database";
+
+// An opening family, as the first of the lichess "OpeningTags" of a puzzle
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub struct Openings {
+    name: Cow<'static, str>,
 }
 
 impl Openings {
-    pub const ALL: [Openings; 57] = [
-        Openings::Any, Openings::Sicilian, Openings::French, Openings::QueensPawnGame, Openings::ItalianGame,
-        Openings::CaroKann, Openings::Scandinavian, Openings::QueensGambitDeclined,
-        Openings::English, Openings::RuyLopez, Openings::IndianDefense, Openings::ScotchGame,
-        Openings::RussianGame, Openings::PhilidorDefense, Openings::ModernDefense, Openings::FourKnightsGame,
-        Openings::KingsGambitAccepted, Openings::ZukertortOpening, Openings::BishopsOpening,
-        Openings::SlavDefense, Openings::PircDefense, Openings::KingsPawnGame, Openings::ViennaGame,
-        Openings::QueensGambitAccepted, Openings::KingsIndianDefense, Openings::Benoni,
-        Openings::NimzowitschDefense, Openings::AlekhineDefense, Openings::NimzoLarsenAttack,
-        Openings::HorwitzDefense, Openings::KingsGambitDeclined, Openings::OwenDefense, Openings::Bird,
-        Openings::Dutch, Openings::NimzoIndianDefense, Openings::VantKruijsOpening, Openings::SemiSlav,
-        Openings::CenterGame, Openings::HungarianOpening, Openings::ThreeKnightsOpening,
-        Openings::PonzianiOpening, Openings::EnglundGambit, Openings::GrunfeldDefense,
-        Openings::BlackmarDiemerGambit, Openings::ElephantGambit, Openings::PolishOpening,
-        Openings::DanishGambit, Openings::KingsIndianAttack, Openings::TrompowskyAttack,
-        Openings::EnglishDefense, Openings::GrobOpening, Openings::RapportJobavaSystem,
-        Openings::TarraschDefense, Openings::CatalanOpening, Openings::Reti, Openings::QueensIndianDefense,
-        Openings::LondonSystem];
+    pub const ANY: Openings = Openings {name: Cow::Borrowed("")};
+
+    pub fn new(tag: &str) -> Openings {
+        Openings::from(tag.to_string())
+    }
 
     pub fn get_field_name(&self) -> &str {
-        match self {
-            Openings::Any => "",
-            Openings::Sicilian => "Sicilian_Defense", Openings::French => "French_Defense",
-            Openings::QueensPawnGame => "Queens_Pawn_Game", Openings::ItalianGame => "Italian_Game",
-            Openings::CaroKann => "Caro-Kann_Defense", Openings::QueensGambitDeclined => "Queens_Gambit_Declined",
-            Openings::Scandinavian => "Scandinavian_Defense", Openings::RuyLopez => "Ruy_Lopez",
-            Openings::English => "English_Opening", Openings::IndianDefense => "Indian_Defense",
-            Openings::ScotchGame => "Scotch_Game", Openings::PhilidorDefense => "Philidor_Defense",
-            Openings::RussianGame => "Russian_Game", Openings::ModernDefense => "Modern_Defense",
-            Openings::FourKnightsGame => "Four_Knights_Game", Openings::KingsGambitAccepted => "Kings_Gambit_Accepted",
-            Openings::SlavDefense => "Slav_Defense", Openings::PircDefense => "Pirc_Defense",
-            Openings::ZukertortOpening => "Zukertort_Opening", Openings::BishopsOpening => "Bishops_Opening",
-            Openings::KingsPawnGame => "Kings_Pawn_Game", Openings::ViennaGame => "Vienna_Game",
-            Openings::KingsIndianDefense => "Kings_Indian_Defense", Openings::QueensGambitAccepted => "Queens_Gambit_Accepted",
-            Openings::Benoni => "Benoni_Defense", Openings::AlekhineDefense => "Alekhine_Defense",
-            Openings::NimzowitschDefense => "Nimzowitsch_Defense", Openings::HorwitzDefense => "Horwitz_Defense",
-            Openings::NimzoLarsenAttack => "Nimzo-Larsen_Attack", Openings::KingsGambitDeclined => "Kings_Gambit_Declined",
-            Openings::NimzoIndianDefense => "Nimzo-Indian_Defense", Openings::Bird => "Bird_Opening",
-            Openings::Dutch => "Dutch_Defense", Openings::SemiSlav => "Semi-Slav_Defense",
-            Openings::OwenDefense => "Owen_Defense", Openings::VantKruijsOpening => "Vant_Kruijs_Opening",
-            Openings::CenterGame => "Center_Game", Openings::HungarianOpening => "Hungarian_Opening",
-            Openings::ThreeKnightsOpening => "Three_Knights_Opening", Openings::PonzianiOpening => "Ponziani_Opening",
-            Openings::EnglundGambit => "Englund_Gambit", Openings::GrunfeldDefense => "Grunfeld_Defense",
-            Openings::BlackmarDiemerGambit => "Blackmar-Diemer_Gambit", Openings::ElephantGambit => "Elephant_Gambit",
-            Openings::PolishOpening => "Polish_Opening", Openings::DanishGambit => "Danish_Gambit",
-            Openings::KingsIndianAttack => "Kings_Indian_Attack", Openings::TrompowskyAttack => "Trompowsky_Attack",
-            Openings::EnglishDefense => "English_Defense", Openings::GrobOpening => "Grob_Opening",
-            Openings::RapportJobavaSystem => "Rapport-Jobava_System", Openings::TarraschDefense => "Tarrasch_Defense",
-            Openings::CatalanOpening => "Catalan_Opening", Openings::Reti => "Reti_Opening",
-            Openings::QueensIndianDefense => "Queens_Indian_Defense", Openings::LondonSystem => "London_System"
-        }
+        &self.name
     }
+
     pub fn get_tr_key(&self) -> &str {
-        match self {
-            Openings::Any => "any_opening",
-            _ => self.get_field_name(),
+        if self == &Openings::ANY {
+            "any_opening"
+        } else {
+            self.get_field_name()
+        }
+    }
+}
+
+// The openings used to be a fixed list, saved in the settings by these names
+const LEGACY_NAMES: [(&str, &str); 56] = [
+    ("Sicilian", "Sicilian_Defense"), ("French", "French_Defense"), ("QueensPawnGame", "Queens_Pawn_Game"),
+    ("ItalianGame", "Italian_Game"), ("CaroKann", "Caro-Kann_Defense"), ("Scandinavian", "Scandinavian_Defense"),
+    ("QueensGambitDeclined", "Queens_Gambit_Declined"), ("English", "English_Opening"), ("RuyLopez", "Ruy_Lopez"),
+    ("IndianDefense", "Indian_Defense"), ("ScotchGame", "Scotch_Game"), ("RussianGame", "Russian_Game"),
+    ("PhilidorDefense", "Philidor_Defense"), ("ModernDefense", "Modern_Defense"), ("FourKnightsGame", "Four_Knights_Game"),
+    ("KingsGambitAccepted", "Kings_Gambit_Accepted"), ("ZukertortOpening", "Zukertort_Opening"),
+    ("BishopsOpening", "Bishops_Opening"), ("SlavDefense", "Slav_Defense"), ("PircDefense", "Pirc_Defense"),
+    ("KingsPawnGame", "Kings_Pawn_Game"), ("ViennaGame", "Vienna_Game"), ("QueensGambitAccepted", "Queens_Gambit_Accepted"),
+    ("KingsIndianDefense", "Kings_Indian_Defense"), ("Benoni", "Benoni_Defense"), ("NimzowitschDefense", "Nimzowitsch_Defense"),
+    ("AlekhineDefense", "Alekhine_Defense"), ("NimzoLarsenAttack", "Nimzo-Larsen_Attack"), ("HorwitzDefense", "Horwitz_Defense"),
+    ("KingsGambitDeclined", "Kings_Gambit_Declined"), ("OwenDefense", "Owen_Defense"), ("Bird", "Bird_Opening"),
+    ("Dutch", "Dutch_Defense"), ("NimzoIndianDefense", "Nimzo-Indian_Defense"), ("VantKruijsOpening", "Vant_Kruijs_Opening"),
+    ("SemiSlav", "Semi-Slav_Defense"), ("CenterGame", "Center_Game"), ("HungarianOpening", "Hungarian_Opening"),
+    ("ThreeKnightsOpening", "Three_Knights_Opening"), ("PonzianiOpening", "Ponziani_Opening"), ("EnglundGambit", "Englund_Gambit"),
+    ("GrunfeldDefense", "Grunfeld_Defense"), ("BlackmarDiemerGambit", "Blackmar-Diemer_Gambit"),
+    ("ElephantGambit", "Elephant_Gambit"), ("PolishOpening", "Polish_Opening"), ("DanishGambit", "Danish_Gambit"),
+    ("KingsIndianAttack", "Kings_Indian_Attack"), ("TrompowskyAttack", "Trompowsky_Attack"), ("EnglishDefense", "English_Defense"),
+    ("GrobOpening", "Grob_Opening"), ("RapportJobavaSystem", "Rapport-Jobava_System"), ("TarraschDefense", "Tarrasch_Defense"),
+    ("CatalanOpening", "Catalan_Opening"), ("Reti", "Reti_Opening"), ("QueensIndianDefense", "Queens_Indian_Defense"),
+    ("LondonSystem", "London_System"),
+];
+
+// "Any" is how the "no opening" option was saved in the settings
+impl From<String> for Openings {
+    fn from(tag: String) -> Openings {
+        if tag.is_empty() || tag == "Any" {
+            Openings::ANY
+        } else if let Some((_, legacy_tag)) = LEGACY_NAMES.iter().find(|(name, _)| *name == tag) {
+            Openings {name: Cow::Borrowed(legacy_tag)}
+        } else {
+            Openings {name: Cow::Owned(tag)}
+        }
+    }
+}
+
+impl From<Openings> for String {
+    fn from(opening: Openings) -> String {
+        if opening == Openings::ANY {
+            String::from("Any")
+        } else {
+            opening.name.into_owned()
         }
     }
 }
@@ -83,13 +90,13 @@ impl DisplayTranslated for Openings {
 }
 
 impl PickListWrapper<Openings> {
-    pub fn get_openings(lang: lang::Language) -> Vec<PickListWrapper<Openings>> {
-        let mut openings_wrapper = Vec::new();
-        for opening in Openings::ALL {
+    pub fn get_openings(lang: lang::Language, catalog: &OpeningCatalog) -> Vec<PickListWrapper<Openings>> {
+        let mut openings_wrapper = vec![PickListWrapper::<Openings> { lang, item: Openings::ANY }];
+        for (family, _) in &catalog.families {
             openings_wrapper.push(
                 PickListWrapper::<Openings> {
                     lang: lang,
-                    item: opening,
+                    item: Openings::new(family),
                 }
             );
         }
@@ -111,279 +118,31 @@ pub struct Variation {
 
 impl Variation {
     pub const ANY_STR: &'static str = "Any_Variation";
-    pub const ANY: Variation = Variation {name: Cow::Borrowed("Any_Variation"), family: Openings::Any};
-
-    const SICILIAN_VARIATIONS: [Variation; 31] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Bowdler_Attack"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Old_Sicilian"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Closed"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Najdorf_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Alapin_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Modern_Variations"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Smith-Morra_Gambit"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_McDonnell_Attack"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Open"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Taimanov_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_French_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Nyezhmetdinov-Rossolimo_Attack"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Accelerated_Dragon"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Dragon_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Kan_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Lowenthal_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Lasker-Pelikan_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Four_Knights_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_OKelly_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Smith-Morra_Gambit_Accepted"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Grand_Prix_Attack"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Hyperaccelerated_Fianchetto"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Hyperaccelerated_Dragon"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Delayed_Alapin_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Wing_Gambit"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Classical_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Staunton-Cochrane_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Marshall_Gambit"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Scheveningen_Variation"), family: Openings::Sicilian},
-        Variation {name: Cow::Borrowed("Sicilian_Defense_Smith-Morra_Gambit_Declined"), family: Openings::Sicilian},
-    ];
-    const FRENCH_VARIATIONS: [Variation; 14] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_Advance_Variation"), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_Knight_Variation"), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_Exchange_Variation"), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_Tarrasch_Variation"), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_Normal_Variation"), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_Winawer_Variation"), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_Rubinstein_Variation"), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_Classical_Variation"), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_La_Bourdonnais_Variation"), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_Queens_Knight"), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_Kings_Indian_Attack"), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_Steiner_Variation"), family: Openings::French},
-        Variation {name: Cow::Borrowed("French_Defense_Two_Knights_Variation"), family: Openings::French},
-    ];
-
-    const QUEENS_PAWN_GAME_VARIATIONS: [Variation; 10] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::QueensPawnGame},
-        Variation {name: Cow::Borrowed("Queens_Pawn_Game_Accelerated_London_System"), family: Openings::QueensPawnGame},
-        Variation {name: Cow::Borrowed("Queens_Pawn_Game_Chigorin_Variation"), family: Openings::QueensPawnGame},
-        Variation {name: Cow::Borrowed("Queens_Pawn_Game_Zukertort_Variation"), family: Openings::QueensPawnGame},
-        Variation {name: Cow::Borrowed("Queens_Pawn_Game_London_System"), family: Openings::QueensPawnGame},
-        Variation {name: Cow::Borrowed("Queens_Pawn_Game_Colle_System"), family: Openings::QueensPawnGame},
-        Variation {name: Cow::Borrowed("Queens_Pawn_Game_Symmetrical_Variation"), family: Openings::QueensPawnGame},
-        Variation {name: Cow::Borrowed("Queens_Pawn_Game_Levitsky_Attack"), family: Openings::QueensPawnGame},
-        Variation {name: Cow::Borrowed("Queens_Pawn_Game_Krause_Variation"), family: Openings::QueensPawnGame},
-        Variation {name: Cow::Borrowed("Queens_Pawn_Game_Torre_Attack"), family: Openings::QueensPawnGame},
-    ];
-
-    const ITALIAN_VARIATIONS: [Variation; 10] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::ItalianGame},
-        Variation {name: Cow::Borrowed("Italian_Game_Two_Knights_Defense"), family: Openings::ItalianGame},
-        Variation {name: Cow::Borrowed("Italian_Game_Classical_Variation"), family: Openings::ItalianGame},
-        Variation {name: Cow::Borrowed("Italian_Game_Giuoco_Pianissimo"), family: Openings::ItalianGame},
-        Variation {name: Cow::Borrowed("Italian_Game_Anti-Fried_Liver_Defense"), family: Openings::ItalianGame},
-        Variation {name: Cow::Borrowed("Italian_Game_Giuoco_Piano"), family: Openings::ItalianGame},
-        Variation {name: Cow::Borrowed("Italian_Game_Evans_Gambit"), family: Openings::ItalianGame},
-        Variation {name: Cow::Borrowed("Italian_Game_Scotch_Gambit"), family: Openings::ItalianGame},
-        Variation {name: Cow::Borrowed("Italian_Game_Blackburne-Kostic_Gambit"), family: Openings::ItalianGame},
-        Variation {name: Cow::Borrowed("Italian_Game_Hungarian_Defense"), family: Openings::ItalianGame},
-    ];
-
-    const CARO_KANN_VARIATIONS: [Variation; 11] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::CaroKann},
-        Variation {name: Cow::Borrowed("Caro-Kann_Defense_Advance_Variation"), family: Openings::CaroKann},
-        Variation {name: Cow::Borrowed("Caro-Kann_Defense_Exchange_Variation"), family: Openings::CaroKann},
-        Variation {name: Cow::Borrowed("Caro-Kann_Defense_Classical_Variation"), family: Openings::CaroKann},
-        Variation {name: Cow::Borrowed("Caro-Kann_Defense_Two_Knights_Attack"), family: Openings::CaroKann},
-        Variation {name: Cow::Borrowed("Caro-Kann_Defense_Panov_Attack"), family: Openings::CaroKann},
-        Variation {name: Cow::Borrowed("Caro-Kann_Defense_Hillbilly_Attack"), family: Openings::CaroKann},
-        Variation {name: Cow::Borrowed("Caro-Kann_Defense_Accelerated_Panov_Attack"), family: Openings::CaroKann},
-        Variation {name: Cow::Borrowed("Caro-Kann_Defense_Maroczy_Variation"), family: Openings::CaroKann},
-        Variation {name: Cow::Borrowed("Caro-Kann_Defense_Tartakower_Variation"), family: Openings::CaroKann},
-        Variation {name: Cow::Borrowed("Caro-Kann_Defense_Karpov_Variation"), family: Openings::CaroKann},
-    ];
-
-    const SCANDINAVIAN_VARIATIONS: [Variation; 7] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::Scandinavian},
-        Variation {name: Cow::Borrowed("Scandinavian_Defense_Mieses-Kotroc_Variation"), family: Openings::Scandinavian},
-        Variation {name: Cow::Borrowed("Scandinavian_Defense_Modern_Variation"), family: Openings::Scandinavian},
-        Variation {name: Cow::Borrowed("Scandinavian_Defense_Main_Line"), family: Openings::Scandinavian},
-        Variation {name: Cow::Borrowed("Scandinavian_Defense_Gubinsky-Melts_Defense"), family: Openings::Scandinavian},
-        Variation {name: Cow::Borrowed("Scandinavian_Defense_Icelandic-Palme_Gambit"), family: Openings::Scandinavian},
-        Variation {name: Cow::Borrowed("Scandinavian_Defense_Panov_Transfer"), family: Openings::Scandinavian},
-    ];
-
-    const QUEENS_GAMBIT_DECLINED_VARIATIONS: [Variation; 14] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Marshall_Defense"), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Albin_Countergambit"), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Exchange_Variation"), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Modern_Variation"), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Chigorin_Defense"), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Ragozin_Defense"), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Queens_Knight_Variation"), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Baltic_Defense"), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Normal_Defense"), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Three_Knights_Variation"), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Harrwitz_Attack"), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Semi-Tarrasch_Defense"), family: Openings::QueensGambitDeclined},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Declined_Tarrasch_Defense"), family: Openings::QueensGambitDeclined},
-    ];
-
-    const ENGLISH_VARIATIONS: [Variation; 7] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::English},
-        Variation {name: Cow::Borrowed("English_Opening_Kings_English_Variation"), family: Openings::English},
-        Variation {name: Cow::Borrowed("English_Opening_Symmetrical_Variation"), family: Openings::English},
-        Variation {name: Cow::Borrowed("English_Opening_Anglo-Indian_Defense"), family: Openings::English},
-        Variation {name: Cow::Borrowed("English_Opening_Agincourt_Defense"), family: Openings::English},
-        Variation {name: Cow::Borrowed("English_Opening_Anglo-Scandinavian_Defense"), family: Openings::English},
-        Variation {name: Cow::Borrowed("English_Opening_Caro-Kann_Defensive_System"), family: Openings::English},
-    ];
-
-    const RUY_LOPEZ_VARIATIONS: [Variation; 12] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::RuyLopez},
-        Variation {name: Cow::Borrowed("Ruy_Lopez_Morphy_Defense"), family: Openings::RuyLopez},
-        Variation {name: Cow::Borrowed("Ruy_Lopez_Berlin_Defense"), family: Openings::RuyLopez},
-        Variation {name: Cow::Borrowed("Ruy_Lopez_Steinitz_Defense"), family: Openings::RuyLopez},
-        Variation {name: Cow::Borrowed("Ruy_Lopez_Classical_Variation"), family: Openings::RuyLopez},
-        Variation {name: Cow::Borrowed("Ruy_Lopez_Exchange_Variation"), family: Openings::RuyLopez},
-        Variation {name: Cow::Borrowed("Ruy_Lopez_Bird_Variation"), family: Openings::RuyLopez},
-        Variation {name: Cow::Borrowed("Ruy_Lopez_Cozio_Defense"), family: Openings::RuyLopez},
-        Variation {name: Cow::Borrowed("Ruy_Lopez_Schliemann_Defense"), family: Openings::RuyLopez},
-        Variation {name: Cow::Borrowed("Ruy_Lopez_Closed"), family: Openings::RuyLopez},
-        Variation {name: Cow::Borrowed("Ruy_Lopez_Open"), family: Openings::RuyLopez},
-        Variation {name: Cow::Borrowed("Ruy_Lopez_Marshall_Attack"), family: Openings::RuyLopez},
-    ];
-
-    const INDIAN_DEFENSE_VARIATIONS: [Variation; 4] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::IndianDefense},
-        Variation {name: Cow::Borrowed("Indian_Defense_Budapest_Defense"), family: Openings::IndianDefense},
-        Variation {name: Cow::Borrowed("Indian_Defense_Normal_Variation"), family: Openings::IndianDefense},
-        Variation {name: Cow::Borrowed("Indian_Defense_London_System"), family: Openings::IndianDefense},
-    ];
-
-    const SCOTCH_GAME_VARIATIONS: [Variation; 5] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::ScotchGame},
-        Variation {name: Cow::Borrowed("Scotch_Game_Scotch_Gambit"), family: Openings::ScotchGame},
-        Variation {name: Cow::Borrowed("Scotch_Game_Classical_Variation"), family: Openings::ScotchGame},
-        Variation {name: Cow::Borrowed("Scotch_Game_Schmidt_Variation"), family: Openings::ScotchGame},
-        Variation {name: Cow::Borrowed("Scotch_Game_Goring_Gambit"), family: Openings::ScotchGame},
-    ];
-
-    const RUSSIAN_GAME_VARIATIONS: [Variation; 6] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::RussianGame},
-        Variation {name: Cow::Borrowed("Russian_Game_Stafford_Gambit"), family: Openings::RussianGame},
-        Variation {name: Cow::Borrowed("Russian_Game_Italian_Variation"), family: Openings::RussianGame},
-        Variation {name: Cow::Borrowed("Russian_Game_Three_Knights_Game"), family: Openings::RussianGame},
-        Variation {name: Cow::Borrowed("Russian_Game_Modern_Attack"), family: Openings::RussianGame},
-        Variation {name: Cow::Borrowed("Russian_Game_Classical_Attack"), family: Openings::RussianGame},
-    ];
-
-    const KINGS_GAMBIT_ACCEPTED_VARIATIONS: [Variation; 8] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::KingsGambitAccepted},
-        Variation {name: Cow::Borrowed("Kings_Gambit_Accepted_MacLeod_Defense"), family: Openings::KingsGambitAccepted},
-        Variation {name: Cow::Borrowed("Kings_Gambit_Accepted_Fischer_Defense"), family: Openings::KingsGambitAccepted},
-        Variation {name: Cow::Borrowed("Kings_Gambit_Accepted_Kings_Knights_Gambit"), family: Openings::KingsGambitAccepted},
-        Variation {name: Cow::Borrowed("Kings_Gambit_Accepted_Bishops_Gambit"), family: Openings::KingsGambitAccepted},
-        Variation {name: Cow::Borrowed("Kings_Gambit_Accepted_Cunningham_Defense"), family: Openings::KingsGambitAccepted},
-        Variation {name: Cow::Borrowed("Kings_Gambit_Accepted_Modern_Defense"), family: Openings::KingsGambitAccepted},
-        Variation {name: Cow::Borrowed("Kings_Gambit_Accepted_Schallopp_Defense"), family: Openings::KingsGambitAccepted},
-    ];
-
-    const SLAV_DEFENSE_VARIATIONS: [Variation; 5] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::SlavDefense},
-        Variation {name: Cow::Borrowed("Slav_Defense_Exchange_Variation"), family: Openings::SlavDefense},
-        Variation {name: Cow::Borrowed("Slav_Defense_Three_Knights_Variation"), family: Openings::SlavDefense},
-        Variation {name: Cow::Borrowed("Slav_Defense_Modern_Line"), family: Openings::SlavDefense},
-        Variation {name: Cow::Borrowed("Slav_Defense_Quiet_Variation"), family: Openings::SlavDefense},
-    ];
-
-    const VIENNA_VARIATIONS: [Variation; 4] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::ViennaGame},
-        Variation {name: Cow::Borrowed("Vienna_Game_Vienna_Gambit"), family: Openings::ViennaGame},
-        Variation {name: Cow::Borrowed("Vienna_Game_Stanley_Variation"), family: Openings::ViennaGame},
-        Variation {name: Cow::Borrowed("Vienna_Game_Max_Lange_Defense"), family: Openings::ViennaGame},
-    ];
-
-    const QUEENS_GAMBIT_ACCEPTED_VARIATIONS: [Variation; 3] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::QueensGambitAccepted},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Accepted_Old_Variation"), family: Openings::QueensGambitAccepted},
-        Variation {name: Cow::Borrowed("Queens_Gambit_Accepted_Central_Variation"), family: Openings::QueensGambitAccepted},
-    ];
-
-    const KINGS_INDIAN_DEFENSE_VARIATIONS: [Variation; 6] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::KingsIndianDefense},
-        Variation {name: Cow::Borrowed("Kings_Indian_Defense_Normal_Variation"), family: Openings::KingsIndianDefense},
-        Variation {name: Cow::Borrowed("Kings_Indian_Defense_Orthodox_Variation"), family: Openings::KingsIndianDefense},
-        Variation {name: Cow::Borrowed("Kings_Indian_Defense_Fianchetto_Variation"), family: Openings::KingsIndianDefense},
-        Variation {name: Cow::Borrowed("Kings_Indian_Defense_Samisch_Variation"), family: Openings::KingsIndianDefense},
-        Variation {name: Cow::Borrowed("Kings_Indian_Defense_Four_Pawns_Attack"), family: Openings::KingsIndianDefense},
-    ];
-
-    const BENONI_VARIATIONS: [Variation; 5] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::Benoni},
-        Variation {name: Cow::Borrowed("Benoni_Defense_Old_Benoni"), family: Openings::Benoni},
-        Variation {name: Cow::Borrowed("Benoni_Defense_Benoni_Gambit_Accepted"), family: Openings::Benoni},
-        Variation {name: Cow::Borrowed("Benoni_Defense_French_Benoni"), family: Openings::Benoni},
-        Variation {name: Cow::Borrowed("Benoni_Defense_Modern_Variation"), family: Openings::Benoni},
-    ];
-
-    const KINGS_GAMBIT_DECLINED_VARIATIONS: [Variation; 4] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::KingsGambitDeclined},
-        Variation {name: Cow::Borrowed("Kings_Gambit_Declined_Queens_Knight_Defense"), family: Openings::KingsGambitDeclined},
-        Variation {name: Cow::Borrowed("Kings_Gambit_Declined_Falkbeer_Countergambit"), family: Openings::KingsGambitDeclined},
-        Variation {name: Cow::Borrowed("Kings_Gambit_Declined_Classical_Variation"), family: Openings::KingsGambitDeclined},
-    ];
-
-    const GRUNFELD_VARIATIONS: [Variation; 3] = [
-        Variation {name: Cow::Borrowed(Variation::ANY_STR), family: Openings::GrunfeldDefense},
-        Variation {name: Cow::Borrowed("Grunfeld_Defense_Exchange_Variation"), family: Openings::GrunfeldDefense},
-        Variation {name: Cow::Borrowed("Grunfeld_Defense_Three_Knights_Variation"), family: Openings::GrunfeldDefense},
-    ];
-
+    pub const ANY: Variation = Variation {name: Cow::Borrowed("Any_Variation"), family: Openings::ANY};
 }
 
 impl DisplayTranslated for Variation {
     fn to_str_tr(&self) -> &str {
         &self.name
     }
+
+    // The variation tags repeat the family name, which is already in the other pick list
+    fn fallback(&self) -> String {
+        let name = self.name.strip_prefix(self.family.get_field_name()).unwrap_or(&self.name);
+        name.trim_start_matches('_').replace('_', " ")
+    }
 }
 
 impl PickListWrapper<Variation> {
-    pub fn get_variations(lang: lang::Language, family: Option<&Openings>) -> Vec<PickListWrapper<Variation>> {
-        let mut openings_wrapper = Vec::new();
-        if let Some(family) = family {
-            let variations: Vec<Variation> = match family {
-                Openings::Sicilian => Variation::SICILIAN_VARIATIONS.to_vec(),
-                Openings::French => Variation::FRENCH_VARIATIONS.to_vec(),
-                Openings::QueensPawnGame => Variation::QUEENS_PAWN_GAME_VARIATIONS.to_vec(),
-                Openings::ItalianGame => Variation::ITALIAN_VARIATIONS.to_vec(),
-                Openings::CaroKann => Variation::CARO_KANN_VARIATIONS.to_vec(),
-                Openings::Scandinavian => Variation::SCANDINAVIAN_VARIATIONS.to_vec(),
-                Openings::QueensGambitDeclined => Variation::QUEENS_GAMBIT_DECLINED_VARIATIONS.to_vec(),
-                Openings::English => Variation::ENGLISH_VARIATIONS.to_vec(),
-                Openings::RuyLopez => Variation::RUY_LOPEZ_VARIATIONS.to_vec(),
-                Openings::IndianDefense => Variation::INDIAN_DEFENSE_VARIATIONS.to_vec(),
-                Openings::ScotchGame => Variation::SCOTCH_GAME_VARIATIONS.to_vec(),
-                Openings::RussianGame => Variation::RUSSIAN_GAME_VARIATIONS.to_vec(),
-                Openings::KingsGambitAccepted => Variation::KINGS_GAMBIT_ACCEPTED_VARIATIONS.to_vec(),
-                Openings::SlavDefense => Variation::SLAV_DEFENSE_VARIATIONS.to_vec(),
-                Openings::ViennaGame => Variation::VIENNA_VARIATIONS.to_vec(),
-                Openings::QueensGambitAccepted => Variation::QUEENS_GAMBIT_ACCEPTED_VARIATIONS.to_vec(),
-                Openings::KingsIndianDefense => Variation::KINGS_INDIAN_DEFENSE_VARIATIONS.to_vec(),
-                Openings::Benoni => Variation::BENONI_VARIATIONS.to_vec(),
-                Openings::KingsGambitDeclined => Variation::KINGS_GAMBIT_DECLINED_VARIATIONS.to_vec(),
-                Openings::GrunfeldDefense => Variation::GRUNFELD_VARIATIONS.to_vec(),
-                _ => vec![Variation::ANY],
-            };
-            for variation in variations {
-                openings_wrapper.push(
-                    PickListWrapper::<Variation> {
-                        lang: lang,
-                        item: variation,
-                    }
-                );
-            }
+    pub fn get_variations(lang: lang::Language, catalog: &OpeningCatalog, family: &Openings) -> Vec<PickListWrapper<Variation>> {
+        let mut openings_wrapper = vec![PickListWrapper::<Variation> { lang, item: Variation::ANY }];
+        for variation in catalog.variations(family) {
+            openings_wrapper.push(
+                PickListWrapper::<Variation> {
+                    lang,
+                    item: Variation {name: Cow::Owned(variation.clone()), family: family.clone()},
+                }
+            );
         }
         openings_wrapper
     }
@@ -395,3 +154,102 @@ impl PickListWrapper<Variation> {
         }
     }
 }
+
+// The opening families and variations found in the puzzles, so the search
+// can offer whatever lichess tags the games with, without us listing them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpeningCatalog {
+    source: String,
+    source_size: u64,
+    // The most common families (and variations) first
+    families: Vec<(String, Vec<String>)>,
+}
+
+impl OpeningCatalog {
+    // The catalog as it was the last time it was built, it may be outdated
+    pub fn cached() -> OpeningCatalog {
+        std::fs::File::open(CATALOG_CACHE).ok()
+            .and_then(|file| serde_json::from_reader(std::io::BufReader::new(file)).ok())
+            .unwrap_or_default()
+    }
+
+    // Rebuilds the catalog if the puzzles changed since it was cached,
+    // reporting how much of the csv was read when it has to scan it.
+    pub fn load(report: &mut dyn FnMut(u64)) -> OpeningCatalog {
+        let (source, source_size) = OpeningCatalog::current_source();
+        let cached = OpeningCatalog::cached();
+        if cached.source == source && cached.source_size == source_size && !cached.families.is_empty() {
+            return cached;
+        }
+        let catalog = OpeningCatalog::build(source, source_size, report);
+        if let Ok(file) = std::fs::File::create(CATALOG_CACHE) {
+            if serde_json::to_writer(file, &catalog).is_err() {
+                println!("Error saving the openings catalog.");
+            }
+        }
+        catalog
+    }
+
+    pub fn variations(&self, family: &Openings) -> &[String] {
+        self.families.iter()
+            .find(|(name, _)| name == family.get_field_name())
+            .map(|(_, variations)| variations.as_slice())
+            .unwrap_or(&[])
+    }
+
+    // The imported puzzles are used when available, like in the search
+    fn current_source() -> (String, u64) {
+        if db::has_imported_puzzles() {
+            (String::from(DB_SOURCE), db::count_puzzles())
+        } else {
+            let path = config::SETTINGS.puzzle_db_location.clone();
+            let size = std::fs::metadata(&path).map_or(0, |metadata| metadata.len());
+            (path, size)
+        }
+    }
+
+    fn build(source: String, source_size: u64, report: &mut dyn FnMut(u64)) -> OpeningCatalog {
+        // family -> (puzzles, variation -> puzzles)
+        let mut counts: HashMap<String, (usize, HashMap<String, usize>)> = HashMap::new();
+        let mut add_tags = |tags: &str, puzzles: usize| {
+            let mut tags = tags.split_whitespace();
+            if let Some(family) = tags.next() {
+                let (family_count, variations) = counts.entry(family.to_string()).or_default();
+                *family_count += puzzles;
+                if let Some(variation) = tags.next() {
+                    *variations.entry(variation.to_string()).or_default() += puzzles;
+                }
+            }
+        };
+        if source == DB_SOURCE {
+            for (tags, puzzles) in db::opening_tag_counts().unwrap_or_default() {
+                add_tags(&tags, puzzles as usize);
+            }
+        } else if let Ok(mut dump) = PuzzleDump::open(&source) {
+            let mut percent = 0;
+            let mut record = csv::StringRecord::new();
+            while let Ok(true) = dump.reader.read_record(&mut record) {
+                if let Ok(puzzle) = record.deserialize::<config::Puzzle>(None) {
+                    add_tags(&puzzle.opening, 1);
+                }
+                if dump.percent_read() != percent {
+                    percent = dump.percent_read();
+                    report(percent);
+                }
+            }
+        }
+
+        let mut families: Vec<(String, usize, Vec<String>)> = counts.into_iter().map(|(family, (family_count, variations))| {
+            let mut variations: Vec<(String, usize)> = variations.into_iter().collect();
+            variations.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            (family, family_count, variations.into_iter().map(|(variation, _)| variation).collect())
+        }).collect();
+        families.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        OpeningCatalog {
+            source,
+            source_size,
+            families: families.into_iter().map(|(family, _, variations)| (family, variations)).collect(),
+        }
+    }
+}
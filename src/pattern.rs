@@ -0,0 +1,184 @@
+use chess::{BitBoard, Board, Color, Piece, Square};
+use std::str::FromStr;
+
+// Pieces counted in a material signature (kings are always there)
+const MATERIAL_PIECES: [Piece; 5] = [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight, Piece::Pawn];
+
+// A description of the board content to search for, made of terms separated by spaces:
+// "RP vs R"   material of white vs material of black (kings are implicit, "+" is ignored)
+// "kg8"       a piece on a square, uppercase for white and lowercase for black as in FEN
+// "-Qq"       no piece of those types on the board
+// "5ppp/"     a fragment of the piece placement field of the FEN
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PositionPattern {
+    material: Option<([u32; 5], [u32; 5])>,
+    pieces_on: Vec<(Piece, Color, Square)>,
+    absent: Vec<(Piece, Color)>,
+    fen_fragments: Vec<String>,
+}
+
+impl PositionPattern {
+    // Returns None if the pattern can't be understood
+    pub fn parse(pattern: &str) -> Option<PositionPattern> {
+        let tokens: Vec<&str> = pattern.split_whitespace().collect();
+        let mut position = PositionPattern::default();
+        let mut i = 0;
+        while i < tokens.len() {
+            if tokens.get(i + 1).is_some_and(|token| token.eq_ignore_ascii_case("vs")) {
+                let white = parse_material(tokens[i])?;
+                let black = parse_material(tokens.get(i + 2)?)?;
+                position.material = Some((white, black));
+                i += 3;
+                continue;
+            }
+            let token = tokens[i];
+            if token.contains('/') {
+                position.fen_fragments.push(token.to_string());
+            } else if let Some(pieces) = token.strip_prefix('-') {
+                if pieces.is_empty() {
+                    return None;
+                }
+                for c in pieces.chars() {
+                    position.absent.push(piece_from_char(c)?);
+                }
+            } else {
+                let mut chars = token.chars();
+                let (piece, color) = piece_from_char(chars.next()?)?;
+                let square = Square::from_str(chars.as_str()).ok()?;
+                position.pieces_on.push((piece, color, square));
+            }
+            i += 1;
+        }
+        Some(position)
+    }
+
+    pub fn matches(&self, board: &Board) -> bool {
+        if let Some((white, black)) = &self.material {
+            for (index, piece) in MATERIAL_PIECES.iter().enumerate() {
+                if count_pieces(board, *piece, Color::White) != white[index] ||
+                        count_pieces(board, *piece, Color::Black) != black[index] {
+                    return false;
+                }
+            }
+        }
+        let pieces_match = self.pieces_on.iter().all(|(piece, color, square)| {
+            board.piece_on(*square) == Some(*piece) && board.color_on(*square) == Some(*color)
+        });
+        let absent_match = self.absent.iter().all(|(piece, color)| count_pieces(board, *piece, *color) == 0);
+        let fen = board.to_string();
+        let placement = fen.split_whitespace().next().unwrap_or_default();
+        let fen_match = self.fen_fragments.iter().all(|fragment| placement.contains(fragment.as_str()));
+
+        pieces_match && absent_match && fen_match
+    }
+}
+
+fn count_pieces(board: &Board, piece: Piece, color: Color) -> u32 {
+    let bitboard: BitBoard = *board.pieces(piece) & *board.color_combined(color);
+    bitboard.popcnt()
+}
+
+fn piece_from_char(c: char) -> Option<(Piece, Color)> {
+    let color = if c.is_ascii_uppercase() {
+        Color::White
+    } else {
+        Color::Black
+    };
+    let piece = match c.to_ascii_lowercase() {
+        'k' => Piece::King,
+        'q' => Piece::Queen,
+        'r' => Piece::Rook,
+        'b' => Piece::Bishop,
+        'n' => Piece::Knight,
+        'p' => Piece::Pawn,
+        _ => return None,
+    };
+    Some((piece, color))
+}
+
+// Counts the pieces of one side in a signature like "KRP" or "R+P"
+fn parse_material(token: &str) -> Option<[u32; 5]> {
+    let mut material = [0; 5];
+    for c in token.chars() {
+        match c.to_ascii_uppercase() {
+            'K' | '+' => (),
+            'Q' => material[0] += 1,
+            'R' => material[1] += 1,
+            'B' => material[2] += 1,
+            'N' => material[3] += 1,
+            'P' => material[4] += 1,
+            _ => return None,
+        }
+    }
+    Some(material)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // White has a rook and two pawns, black a rook and three pawns
+    const ROOK_ENDING: &str = "r5k1/5ppp/8/8/8/8/5PP1/R5K1 w - - 0 1";
+
+    fn matches(pattern: &str, fen: &str) -> bool {
+        let board = Board::from_str(fen).unwrap();
+        PositionPattern::parse(pattern).unwrap().matches(&board)
+    }
+
+    #[test]
+    fn material() {
+        assert!(matches("RPP vs RPPP", ROOK_ENDING));
+        assert!(matches("KR+PP vs kr+ppp", ROOK_ENDING));
+        assert!(!matches("RP vs R", ROOK_ENDING));
+        assert!(!matches("RPPP vs RPP", ROOK_ENDING));
+    }
+
+    #[test]
+    fn pieces_on_squares() {
+        assert!(matches("kg8", ROOK_ENDING));
+        assert!(matches("kg8 Kg1 Ra1 ph7", ROOK_ENDING));
+        assert!(!matches("Kg8", ROOK_ENDING));
+        assert!(!matches("ph2", ROOK_ENDING));
+    }
+
+    #[test]
+    fn absent_pieces() {
+        assert!(matches("-Qq", ROOK_ENDING));
+        assert!(matches("-BNbn", ROOK_ENDING));
+        assert!(!matches("-Qr", ROOK_ENDING));
+        assert!(!matches("-P", ROOK_ENDING));
+    }
+
+    #[test]
+    fn fen_fragments() {
+        assert!(matches("5ppp/", ROOK_ENDING));
+        assert!(matches("5PP1/", ROOK_ENDING));
+        assert!(!matches("5PPP/", ROOK_ENDING));
+    }
+
+    #[test]
+    fn all_terms_together() {
+        assert!(matches("RPP vs RPPP kg8 -Qq 5ppp/", ROOK_ENDING));
+        assert!(!matches("RPP vs RPPP kg8 -Qq 5PPP/", ROOK_ENDING));
+        assert_eq!(PositionPattern::parse(""), Some(PositionPattern::default()));
+    }
+
+    // The pattern is about the board only, it matches whichever side is to move
+    // and tells the white pieces from the black ones.
+    #[test]
+    fn both_sides() {
+        let black_to_move = ROOK_ENDING.replace(" w ", " b ");
+        for fen in [ROOK_ENDING, black_to_move.as_str()] {
+            assert!(matches("RPP vs RPPP Kg1 kg8 5ppp/", fen));
+            assert!(!matches("RPPP vs RPP", fen));
+            assert!(!matches("Kg8 kg1", fen));
+        }
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        for pattern in ["RP vs", "RX vs R", "RP vs R7", "kz9", "k", "Xe4", "-", "-Z", "e4", "vs R", "ké4", "♔g1"] {
+            assert_eq!(PositionPattern::parse(pattern), None, "{}", pattern);
+        }
+    }
+}
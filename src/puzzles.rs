@@ -1,10 +1,12 @@
-use iced::widget::{Container, column as col, row, Scrollable, Text, TextInput, Button};
+use iced::widget::{Container, column as col, row, Column, Scrollable, Text, TextInput, Button};
 use iced::{Element};
 use iced::{alignment, Command, Alignment, Length};
-use chess::{Color, Piece};
+use chess::{Board, ChessMove, Color, Piece, Square};
 use iced_aw::TabLabel;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
-use crate::{Message, Tab, config, styles, lang};
+use crate::{Message, Tab, config, db, styles, lang, rating::UserRating};
 
 #[derive(Debug, Clone)]
 pub enum PuzzleMessage {
@@ -12,6 +14,10 @@ pub enum PuzzleMessage {
     CopyText(String),
     OpenLink(String),
     ExportToPDF,
+    ToggleSession,
+    // Handled by the main window, with the id of the puzzle
+    ReplayPuzzle(String),
+    AnalyzePuzzle(String),
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -19,6 +25,70 @@ pub enum GameStatus {
     Playing, PuzzleEnded, NoPuzzles, 
 }
 
+// The try at the puzzle on the board, it goes to the solve history
+// when the puzzle is finished or left behind after a mistake.
+#[derive(Debug, Clone)]
+pub struct PuzzleAttempt {
+    pub puzzle_id: String,
+    pub puzzle_rating: i32,
+    pub puzzle_deviation: i32,
+    pub themes: String,
+    pub opening: String,
+    pub started: Instant,
+    pub wrong_moves: i32,
+    pub hints: i32,
+}
+
+impl PuzzleAttempt {
+    pub fn new(puzzle: &config::Puzzle) -> Self {
+        Self {
+            puzzle_id: puzzle.puzzle_id.clone(),
+            puzzle_rating: puzzle.rating,
+            puzzle_deviation: puzzle.rating_deviation,
+            themes: puzzle.themes.clone(),
+            opening: puzzle.opening.clone(),
+            started: Instant::now(),
+            wrong_moves: 0,
+            hints: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Solved, SolvedWithHints, Failed,
+}
+
+impl Outcome {
+    // Only a solve without hints counts as solved for the reviews and the rating
+    pub fn of(solved: bool, hints: i32) -> Outcome {
+        if !solved {
+            Outcome::Failed
+        } else if hints > 0 {
+            Outcome::SolvedWithHints
+        } else {
+            Outcome::Solved
+        }
+    }
+
+    pub fn get_tr_key(&self) -> &str {
+        match self {
+            Outcome::Solved => "outcome_solved",
+            Outcome::SolvedWithHints => "outcome_hints",
+            Outcome::Failed => "outcome_failed",
+        }
+    }
+}
+
+// The last result of a puzzle of the current set, for the session review
+#[derive(Debug, Clone)]
+pub struct SessionEntry {
+    pub puzzle_id: String,
+    pub rating: i32,
+    pub outcome: Outcome,
+    pub time_taken: Duration,
+}
+
 #[derive(Debug, Clone)]
 pub struct PuzzleTab {
     pub puzzles: Vec<config::Puzzle>,
@@ -27,6 +97,14 @@ pub struct PuzzleTab {
     pub current_puzzle_side: Color,
     pub game_status: GameStatus,
     pub current_puzzle_fen: String,
+    pub attempt: Option<PuzzleAttempt>,
+    pub user_rating: UserRating,
+    pub rating_change: Option<f64>,
+    // Set when the puzzles came from the "at my level" search
+    pub level_window: Option<i32>,
+    // The puzzles attempted since the set was loaded, in the order they were first tried
+    pub session: Vec<SessionEntry>,
+    pub show_session: bool,
     pub lang: lang::Language,
 }
 
@@ -39,6 +117,12 @@ impl PuzzleTab {
             current_puzzle_side: Color::White,
             game_status: GameStatus::NoPuzzles,
             current_puzzle_fen: String::new(),
+            attempt: None,
+            user_rating: db::current_rating(),
+            rating_change: None,
+            level_window: None,
+            session: Vec::new(),
+            show_session: false,
             lang: config::SETTINGS.lang,
         }
     }
@@ -54,6 +138,11 @@ impl PuzzleTab {
                 Command::none()
             } PuzzleMessage::ExportToPDF => {
                 Command::perform(PuzzleTab::export(), Message::ExportPDF)
+            } PuzzleMessage::ToggleSession => {
+                self.show_session = !self.show_session;
+                Command::none()
+            } PuzzleMessage::ReplayPuzzle(_) | PuzzleMessage::AnalyzePuzzle(_) => {
+                Command::none()
             }
         }
     }
@@ -78,9 +167,154 @@ impl PuzzleTab {
         promotion
     }
 
+    // Starts timing the current puzzle, closing the attempt at the
+    // previous one if it was left unfinished.
+    pub fn start_attempt(&mut self) {
+        self.abandon_attempt();
+        self.attempt = self.puzzles.get(self.current_puzzle)
+            .map(PuzzleAttempt::new);
+    }
+
+    // The puzzle was solved, but it only counts as a success
+    // if it was done without any wrong move.
+    pub fn finish_attempt(&mut self) {
+        if let Some(attempt) = self.attempt.take() {
+            self.save_attempt(&attempt, attempt.wrong_moves == 0);
+        }
+    }
+
+    // Just skipping a puzzle isn't recorded, but leaving it after a mistake
+    // or after asking for a hint counts as a failure.
+    pub fn abandon_attempt(&mut self) {
+        if let Some(attempt) = self.attempt.take() {
+            if attempt.wrong_moves > 0 || attempt.hints > 0 {
+                self.save_attempt(&attempt, false);
+            }
+        }
+    }
+
+    fn save_attempt(&mut self, attempt: &PuzzleAttempt, solved: bool) {
+        db::save_attempt(attempt, solved);
+        let outcome = Outcome::of(solved, attempt.hints);
+        let entry = SessionEntry {
+            puzzle_id: attempt.puzzle_id.clone(),
+            rating: attempt.puzzle_rating,
+            outcome,
+            time_taken: attempt.started.elapsed(),
+        };
+        match self.session.iter_mut().find(|entry| entry.puzzle_id == attempt.puzzle_id) {
+            Some(existing) => *existing = entry,
+            None => self.session.push(entry),
+        }
+        // A solve that needed hints still goes into the history as solved (with its hint
+        // count), but it shouldn't push the next review back or raise the rating.
+        let clean = outcome == Outcome::Solved;
+        if let Some(puzzle) = self.puzzles.iter().find(|puzzle| puzzle.puzzle_id == attempt.puzzle_id) {
+            db::schedule_review(puzzle, clean);
+        }
+        let new_rating = self.user_rating.update(attempt.puzzle_rating as f64, attempt.puzzle_deviation as f64, clean);
+        db::save_rating(&attempt.puzzle_id, &new_rating);
+        self.rating_change = Some(new_rating.rating - self.user_rating.rating);
+        self.user_rating = new_rating;
+        self.recenter(self.current_puzzle + 1);
+    }
+
+    // Brings the puzzles within the level window of the user's rating to the front
+    // (keeping their order), or the closest ones if there's none left in the window.
+    pub fn recenter(&mut self, from: usize) {
+        if let Some(window) = self.level_window {
+            let rating = self.user_rating.rating.round() as i32;
+            if let Some(remaining) = self.puzzles.get_mut(from..) {
+                if remaining.iter().any(|puzzle| (puzzle.rating - rating).abs() <= window) {
+                    remaining.sort_by_key(|puzzle| (puzzle.rating - rating).abs() > window);
+                } else {
+                    remaining.sort_by_key(|puzzle| (puzzle.rating - rating).abs());
+                }
+            }
+        }
+    }
+
+    // The rating and how much it changed with the last attempt, e.g. "1523 (+12)".
+    pub fn rating_status(&self) -> String {
+        let mut status = lang::tr(&self.lang, "your_rating") + &self.user_rating.to_display();
+        if let Some(change) = self.rating_change {
+            status.push_str(&format!(" ({:+.0})", change));
+        }
+        status
+    }
+
+    pub fn add_wrong_move(&mut self) {
+        if let Some(attempt) = &mut self.attempt {
+            attempt.wrong_moves += 1;
+        }
+    }
+
+    pub fn add_hint(&mut self) {
+        if let Some(attempt) = &mut self.attempt {
+            attempt.hints += 1;
+        }
+    }
+
     pub fn is_playing(&self) -> bool {
         self.game_status != GameStatus::NoPuzzles
     }
+
+    pub fn puzzle_index(&self, puzzle_id: &str) -> Option<usize> {
+        self.puzzles.iter().position(|puzzle| puzzle.puzzle_id == puzzle_id)
+    }
+
+    fn session_review(&self) -> Column<'_, PuzzleMessage, iced::Renderer<styles::Theme>> {
+        let count = |outcome| self.session.iter().filter(|entry| entry.outcome == outcome).count();
+        let summary = format!("{}: {}   {}: {}   {}: {}",
+            lang::tr(&self.lang, "outcome_solved"), count(Outcome::Solved),
+            lang::tr(&self.lang, "outcome_hints"), count(Outcome::SolvedWithHints),
+            lang::tr(&self.lang, "outcome_failed"), count(Outcome::Failed));
+
+        let mut entries = Column::new().spacing(5);
+        if self.session.is_empty() {
+            entries = entries.push(Text::new(lang::tr(&self.lang, "session_empty")));
+        }
+        for (number, entry) in self.session.iter().enumerate() {
+            entries = entries.push(row![
+                Text::new((number + 1).to_string()).width(Length::FillPortion(1)),
+                Text::new(&entry.puzzle_id).width(Length::FillPortion(2)),
+                Text::new(entry.rating.to_string()).width(Length::FillPortion(1)),
+                Text::new(lang::tr(&self.lang, entry.outcome.get_tr_key())).width(Length::FillPortion(2)),
+                Text::new(format!("{:.1}s", entry.time_taken.as_secs_f64())).width(Length::FillPortion(1)),
+                Button::new(Text::new(lang::tr(&self.lang, "replay"))).padding(3).on_press(PuzzleMessage::ReplayPuzzle(entry.puzzle_id.clone())),
+                Button::new(Text::new(lang::tr(&self.lang, "analyze"))).padding(3).on_press(PuzzleMessage::AnalyzePuzzle(entry.puzzle_id.clone())),
+            ].spacing(5).align_items(Alignment::Center));
+        }
+        col![
+            Text::new(lang::tr(&self.lang, "session_review")),
+            Text::new(summary),
+            Scrollable::new(entries).height(Length::Fill),
+        ].spacing(10).align_items(Alignment::Center)
+    }
+
+    // How long the current puzzle has been on the board
+    pub fn time_on_puzzle(&self) -> Option<Duration> {
+        self.attempt.as_ref().map(|attempt| attempt.started.elapsed())
+    }
+
+    // The moves played since the puzzle was shown (so after the opponent's first one)
+    pub fn moves_played(&self, lang: &lang::Language) -> String {
+        let mut moves = Vec::new();
+        if let Some(puzzle) = self.puzzles.get(self.current_puzzle) {
+            if let Ok(mut board) = Board::from_str(&puzzle.fen) {
+                for (index, notation) in puzzle.moves.split_whitespace().enumerate().take(self.current_puzzle_move) {
+                    if index > 0 {
+                        moves.push(config::coord_to_san(&board, String::from(notation), lang).unwrap_or_else(|| String::from(notation)));
+                    }
+                    let movement = ChessMove::new(
+                        Square::from_str(&notation[..2]).unwrap(),
+                        Square::from_str(&notation[2..4]).unwrap(), PuzzleTab::check_promotion(notation));
+                    board = board.make_move_new(movement);
+                }
+            }
+        }
+        moves.join(" ")
+    }
 }
 
 impl Tab for PuzzleTab {
@@ -95,6 +329,18 @@ impl Tab for PuzzleTab {
     }
 
     fn content(&self) -> Element<Message, iced::Renderer<styles::Theme>> {
+        let toggle_label = if self.show_session {
+            lang::tr(&self.lang, "puzzle_info")
+        } else {
+            lang::tr(&self.lang, "session_review")
+        };
+        let toggle = Button::new(Text::new(toggle_label)).padding(5).on_press(PuzzleMessage::ToggleSession);
+        if self.show_session {
+            let content: Element<PuzzleMessage, iced::Renderer<styles::Theme>> = Container::new(
+                col![toggle, self.session_review()].spacing(10).align_items(Alignment::Center)
+            ).align_x(alignment::Horizontal::Center).height(Length::Fill).into();
+            return content.map(Message::PuzzleInfo);
+        }
         let col_puzzle_info = if !self.puzzles.is_empty() && self.current_puzzle < self.puzzles.len() {
             Scrollable::new(col![
                 Text::new(lang::tr(&self.lang, "puzzle_link")),
@@ -138,9 +384,23 @@ impl Tab for PuzzleTab {
                     .width(Length::Fill)
                 ].spacing(10))
         };
-        let content: Element<PuzzleMessage, iced::Renderer<styles::Theme>> = Container::new(col_puzzle_info)
-            .align_x(alignment::Horizontal::Center).height(Length::Fill).into();
+        let content: Element<PuzzleMessage, iced::Renderer<styles::Theme>> = Container::new(
+            col![toggle, col_puzzle_info].spacing(10).align_items(Alignment::Center)
+        ).align_x(alignment::Horizontal::Center).height(Length::Fill).into();
 
         content.map(Message::PuzzleInfo)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attempt_outcomes() {
+        assert_eq!(Outcome::of(true, 0), Outcome::Solved);
+        assert_eq!(Outcome::of(true, 2), Outcome::SolvedWithHints);
+        assert_eq!(Outcome::of(false, 0), Outcome::Failed);
+        assert_eq!(Outcome::of(false, 1), Outcome::Failed);
+    }
+}
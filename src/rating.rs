@@ -0,0 +1,145 @@
+use std::f64::consts::PI;
+
+// Glicko-2 as described in http://www.glicko.net/glicko/glicko2.pdf,
+// each puzzle is a rating period of its own (as lichess does).
+const SCALE: f64 = 173.7178;
+const TAU: f64 = 0.75;
+const EPSILON: f64 = 0.000001;
+
+pub const DEFAULT_RATING: f64 = 1500.;
+pub const DEFAULT_VOLATILITY: f64 = 0.06;
+pub const MAX_DEVIATION: f64 = 350.;
+const MIN_DEVIATION: f64 = 45.;
+// Lichess shows the rating with a "?" while the deviation is above this.
+const PROVISIONAL_DEVIATION: f64 = 110.;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UserRating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Default for UserRating {
+    fn default() -> Self {
+        Self {
+            rating: DEFAULT_RATING,
+            deviation: MAX_DEVIATION,
+            volatility: DEFAULT_VOLATILITY,
+        }
+    }
+}
+
+impl UserRating {
+    // The puzzle is the opponent, solving it is a win.
+    pub fn update(&self, puzzle_rating: f64, puzzle_deviation: f64, solved: bool) -> Self {
+        let mu = (self.rating - DEFAULT_RATING) / SCALE;
+        let phi = self.deviation / SCALE;
+        let mu_puzzle = (puzzle_rating - DEFAULT_RATING) / SCALE;
+        let phi_puzzle = puzzle_deviation / SCALE;
+        let score = if solved { 1. } else { 0. };
+
+        let g = 1. / (1. + 3. * phi_puzzle.powi(2) / PI.powi(2)).sqrt();
+        let expected = 1. / (1. + (-g * (mu - mu_puzzle)).exp());
+        let v = 1. / (g.powi(2) * expected * (1. - expected));
+        let delta = v * g * (score - expected);
+
+        let volatility = self.new_volatility(phi, v, delta);
+        let phi_star = (phi.powi(2) + volatility.powi(2)).sqrt();
+        let new_phi = 1. / (1. / phi_star.powi(2) + 1. / v).sqrt();
+        let new_mu = mu + new_phi.powi(2) * g * (score - expected);
+
+        Self {
+            rating: new_mu * SCALE + DEFAULT_RATING,
+            deviation: (new_phi * SCALE).clamp(MIN_DEVIATION, MAX_DEVIATION),
+            volatility,
+        }
+    }
+
+    // Step 5 of the paper, finding the new volatility with the Illinois algorithm.
+    fn new_volatility(&self, phi: f64, v: f64, delta: f64) -> f64 {
+        let a = self.volatility.powi(2).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            ex * (delta.powi(2) - phi.powi(2) - v - ex) / (2. * (phi.powi(2) + v + ex).powi(2))
+                - (x - a) / TAU.powi(2)
+        };
+
+        let mut lower = a;
+        let mut upper = if delta.powi(2) > phi.powi(2) + v {
+            (delta.powi(2) - phi.powi(2) - v).ln()
+        } else {
+            let mut k = 1.;
+            while f(a - k * TAU) < 0. {
+                k += 1.;
+            }
+            a - k * TAU
+        };
+        let mut f_lower = f(lower);
+        let mut f_upper = f(upper);
+        while (upper - lower).abs() > EPSILON {
+            let c = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+            let f_c = f(c);
+            if f_c * f_upper <= 0. {
+                lower = upper;
+                f_lower = f_upper;
+            } else {
+                f_lower /= 2.;
+            }
+            upper = c;
+            f_upper = f_c;
+        }
+        (lower / 2.).exp()
+    }
+
+    pub fn is_provisional(&self) -> bool {
+        self.deviation > PROVISIONAL_DEVIATION
+    }
+
+    pub fn to_display(self) -> String {
+        let mut text = format!("{:.0}", self.rating);
+        if self.is_provisional() {
+            text.push('?');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 0.01, "{} != {}", actual, expected);
+    }
+
+    // The first game of the example in the paper, as a rating period of its own
+    #[test]
+    fn solving_a_weaker_puzzle() {
+        let rating = UserRating { rating: 1500., deviation: 200., volatility: DEFAULT_VOLATILITY };
+        let new_rating = rating.update(1400., 30., true);
+        assert_close(new_rating.rating, 1563.56);
+        assert_close(new_rating.deviation, 175.40);
+        assert_close(new_rating.volatility, 0.06);
+    }
+
+    #[test]
+    fn failing_a_weaker_puzzle() {
+        let rating = UserRating { rating: 1500., deviation: 200., volatility: DEFAULT_VOLATILITY };
+        let new_rating = rating.update(1400., 30., false);
+        assert_close(new_rating.rating, 1387.26);
+        assert_close(new_rating.deviation, 175.40);
+    }
+
+    #[test]
+    fn the_deviation_stays_within_bounds() {
+        let mut rating = UserRating::default();
+        assert!(rating.is_provisional());
+        assert_eq!(rating.to_display(), "1500?");
+        for _ in 0..500 {
+            rating = rating.update(rating.rating, 50., true);
+            assert!(rating.deviation >= MIN_DEVIATION && rating.deviation <= MAX_DEVIATION);
+        }
+        assert!(!rating.is_provisional());
+    }
+}
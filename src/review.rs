@@ -0,0 +1,86 @@
+// SM-2 (https://super-memory.com/english/ol/sm2.htm) scheduling for the
+// review queue of failed puzzles.
+const DEFAULT_EASE: f64 = 2.5;
+const MIN_EASE: f64 = 1.3;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// SM-2 grades answers from 0 to 5, a clean solve is a correct answer after
+// some thought and anything else is a failure.
+const SOLVED_QUALITY: f64 = 4.;
+const FAILED_QUALITY: f64 = 1.;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReviewSchedule {
+    pub ease: f64,
+    pub interval_days: i32,
+    pub repetitions: i32,
+}
+
+impl Default for ReviewSchedule {
+    fn default() -> Self {
+        Self {
+            ease: DEFAULT_EASE,
+            interval_days: 0,
+            repetitions: 0,
+        }
+    }
+}
+
+impl ReviewSchedule {
+    // A failure starts the repetitions over, a solve lengthens the interval.
+    pub fn next(&self, solved: bool) -> Self {
+        let quality = if solved { SOLVED_QUALITY } else { FAILED_QUALITY };
+        let (interval_days, repetitions) = if !solved {
+            (1, 0)
+        } else {
+            match self.repetitions {
+                0 => (1, 1),
+                1 => (6, 2),
+                _ => ((self.interval_days as f64 * self.ease).round() as i32, self.repetitions + 1),
+            }
+        };
+        let ease = (self.ease + 0.1 - (5. - quality) * (0.08 + (5. - quality) * 0.02)).max(MIN_EASE);
+        Self { ease, interval_days, repetitions }
+    }
+
+    pub fn due_at(&self, now: i64) -> i64 {
+        now + self.interval_days as i64 * SECONDS_PER_DAY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_lengthen_the_interval() {
+        let first = ReviewSchedule::default().next(true);
+        assert_eq!((first.interval_days, first.repetitions), (1, 1));
+        let second = first.next(true);
+        assert_eq!((second.interval_days, second.repetitions), (6, 2));
+        let third = second.next(true);
+        assert_eq!((third.interval_days, third.repetitions), ((6. * second.ease).round() as i32, 3));
+        // A clean solve is graded 4, which leaves the ease as it was
+        assert_eq!(third.ease, DEFAULT_EASE);
+    }
+
+    #[test]
+    fn a_failure_starts_over_and_lowers_the_ease() {
+        let learned = ReviewSchedule::default().next(true).next(true).next(true);
+        let failed = learned.next(false);
+        assert_eq!((failed.interval_days, failed.repetitions), (1, 0));
+        assert!(failed.ease < learned.ease);
+
+        let mut schedule = failed;
+        for _ in 0..10 {
+            schedule = schedule.next(false);
+        }
+        assert_eq!(schedule.ease, MIN_EASE);
+    }
+
+    #[test]
+    fn due_after_the_interval() {
+        let schedule = ReviewSchedule { interval_days: 6, ..ReviewSchedule::default() };
+        assert_eq!(schedule.due_at(1000), 1000 + 6 * SECONDS_PER_DAY);
+    }
+}
@@ -0,0 +1,219 @@
+use std::time::{Duration, Instant};
+
+use crate::{config, db, lang};
+
+// The run lengths offered in the search tab, in minutes
+pub const RUSH_LENGTHS: [i32; 2] = [3, 5];
+pub const MAX_STRIKES: i32 = 3;
+
+// The first puzzle is around this rating and each one after it is a bit harder
+const START_RATING: i32 = 800;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunKind {
+    // Timed, with the length in minutes
+    Rush(i32),
+    // Until the first mistake
+    Streak,
+}
+
+impl RunKind {
+    pub fn game_mode(self) -> config::GameMode {
+        match self {
+            RunKind::Rush(_) => config::GameMode::Rush,
+            RunKind::Streak => config::GameMode::Streak,
+        }
+    }
+
+    fn max_strikes(self) -> i32 {
+        match self {
+            RunKind::Rush(_) => MAX_STRIKES,
+            RunKind::Streak => 1,
+        }
+    }
+
+    // A streak has no clock, so it climbs slower
+    fn rating_step(self) -> i32 {
+        match self {
+            RunKind::Rush(_) => 25,
+            RunKind::Streak => 10,
+        }
+    }
+}
+
+// A run through puzzles of increasing difficulty. In a puzzle rush we solve as
+// many as possible before the time is up, a wrong move skips the puzzle and three
+// of them end the run. A streak has no time limit but ends on the first mistake.
+#[derive(Debug, Clone)]
+pub struct PuzzleRun {
+    pub kind: RunKind,
+    started: Instant,
+    pub score: i32,
+    pub strikes: i32,
+    // The personal best before this run
+    pub best: i32,
+    pub finished: bool,
+}
+
+impl PuzzleRun {
+    pub fn new(kind: RunKind) -> Self {
+        let best = match kind {
+            RunKind::Rush(minutes) => db::rush_best(minutes),
+            RunKind::Streak => db::streak_best(),
+        };
+        Self {
+            kind,
+            started: Instant::now(),
+            score: 0,
+            strikes: 0,
+            best,
+            finished: false,
+        }
+    }
+
+    // Takes the puzzles of increasing difficulty for the run out of a search result
+    pub fn progressive(kind: RunKind, mut pool: Vec<config::Puzzle>) -> Vec<config::Puzzle> {
+        pool.sort_by_key(|puzzle| puzzle.rating);
+        let mut puzzles = Vec::new();
+        let mut target = START_RATING;
+        for puzzle in pool {
+            if puzzle.rating >= target {
+                puzzles.push(puzzle);
+                target += kind.rating_step();
+            }
+        }
+        puzzles
+    }
+
+    pub fn remaining(&self) -> Option<Duration> {
+        match self.kind {
+            RunKind::Rush(minutes) => Some(Duration::from_secs(minutes as u64 * 60).saturating_sub(self.started.elapsed())),
+            RunKind::Streak => None,
+        }
+    }
+
+    pub fn time_is_up(&self) -> bool {
+        self.remaining().is_some_and(|remaining| remaining.is_zero())
+    }
+
+    // Returns true if it was the last strike
+    pub fn add_strike(&mut self) -> bool {
+        self.strikes += 1;
+        self.strikes >= self.kind.max_strikes()
+    }
+
+    pub fn finish(&mut self) {
+        if !self.finished {
+            self.finished = true;
+            match self.kind {
+                RunKind::Rush(minutes) => db::save_rush_run(minutes, self.score),
+                RunKind::Streak => db::save_streak(self.score),
+            }
+        }
+    }
+
+    pub fn status(&self, lang: &lang::Language) -> String {
+        let score_key = match self.kind {
+            RunKind::Rush(_) => "rush_score",
+            RunKind::Streak => "streak_length",
+        };
+        let score = lang::tr(lang, score_key) + &self.score.to_string();
+        let best = lang::tr(lang, "rush_best") + &self.best.max(self.score).to_string();
+        if self.finished {
+            let mut status = score + "  " + &best;
+            if self.score > self.best {
+                status.push_str("  ");
+                status.push_str(&lang::tr(lang, "rush_new_best"));
+            }
+            status
+        } else if let Some(remaining) = self.remaining() {
+            let remaining = remaining.as_secs();
+            format!("{}{}:{:02}  ", lang::tr(lang, "rush_time"), remaining / 60, remaining % 60) +
+                &score + "  " +
+                &lang::tr(lang, "rush_strikes") + &"X".repeat(self.strikes as usize) +
+                &"-".repeat((MAX_STRIKES - self.strikes).max(0) as usize) + "  " + &best
+        } else {
+            score + "  " + &best
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tests::use_test_profile;
+
+    fn run(kind: RunKind) -> PuzzleRun {
+        PuzzleRun { kind, started: Instant::now(), score: 0, strikes: 0, best: 0, finished: false }
+    }
+
+    fn pool(ratings: &[i32]) -> Vec<config::Puzzle> {
+        ratings.iter().map(|rating| config::Puzzle { rating: *rating, ..config::Puzzle::default() }).collect()
+    }
+
+    fn ratings(puzzles: &[config::Puzzle]) -> Vec<i32> {
+        puzzles.iter().map(|puzzle| puzzle.rating).collect()
+    }
+
+    #[test]
+    fn rush_puzzles_get_harder() {
+        let puzzles = PuzzleRun::progressive(RunKind::Rush(3), pool(&[900, 790, 810, 800, 820, 824, 850, 851]));
+        // The target starts at 800 and goes up 25 after each puzzle taken: 800, 825, 850, 875, 900
+        assert_eq!(ratings(&puzzles), vec![800, 850, 851, 900]);
+    }
+
+    #[test]
+    fn rush_ends_on_the_third_strike() {
+        let mut rush = run(RunKind::Rush(5));
+        assert!(!rush.add_strike());
+        assert!(!rush.add_strike());
+        assert!(rush.add_strike());
+        assert_eq!(rush.strikes, MAX_STRIKES);
+    }
+
+    #[test]
+    fn rush_keeps_the_best_score_of_each_profile() {
+        let _profile = use_test_profile(20);
+        for score in [12, 7] {
+            let mut rush = PuzzleRun::new(RunKind::Rush(3));
+            rush.score = score;
+            rush.finish();
+            // Finishing twice doesn't save the run twice
+            rush.finish();
+        }
+        assert_eq!(db::rush_best(3), 12);
+        assert_eq!(PuzzleRun::new(RunKind::Rush(3)).best, 12);
+        // The other lengths have their own best
+        assert_eq!(db::rush_best(5), 0);
+
+        db::set_active_profile(120);
+        assert_eq!(PuzzleRun::new(RunKind::Rush(3)).best, 0);
+    }
+
+    #[test]
+    fn streak_puzzles_get_harder_slower() {
+        let puzzles = PuzzleRun::progressive(RunKind::Streak, pool(&[790, 800, 805, 810, 815, 820, 850]));
+        // The target starts at 800 and goes up 10 after each puzzle taken
+        assert_eq!(ratings(&puzzles), vec![800, 810, 820, 850]);
+    }
+
+    #[test]
+    fn streak_ends_on_the_first_strike() {
+        assert!(run(RunKind::Streak).add_strike());
+    }
+
+    #[test]
+    fn streak_keeps_the_longest_of_each_profile() {
+        let _profile = use_test_profile(21);
+        for length in [4, 9, 2] {
+            let mut streak = PuzzleRun::new(RunKind::Streak);
+            streak.score = length;
+            streak.finish();
+        }
+        assert_eq!(db::streak_best(), 9);
+        assert_eq!(PuzzleRun::new(RunKind::Streak).best, 9);
+
+        db::set_active_profile(121);
+        assert_eq!(PuzzleRun::new(RunKind::Streak).best, 0);
+    }
+}
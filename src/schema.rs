@@ -1,5 +1,22 @@
 table! {
-    favs (puzzle_id) {
+    attempts (id) {
+        id -> Integer,
+        puzzle_id -> Text,
+        attempted_at -> BigInt,
+        solved -> Bool,
+        wrong_moves -> Integer,
+        hints -> Integer,
+        time_taken_ms -> BigInt,
+        puzzle_rating -> Integer,
+        themes -> Text,
+        opening_tags -> Text,
+        profile_id -> Integer,
+    }
+}
+
+table! {
+    favs (profile_id, puzzle_id) {
+        profile_id -> Integer,
         puzzle_id -> Text,
         fen -> Text,
         moves -> Text,
@@ -12,3 +29,122 @@ table! {
         opening_tags -> Text,
     }
 }
+
+table! {
+    profiles (id) {
+        id -> Integer,
+        name -> Text,
+    }
+}
+
+table! {
+    puzzle_openings (puzzle_id, opening_tag) {
+        puzzle_id -> Text,
+        opening_tag -> Text,
+    }
+}
+
+table! {
+    puzzle_source (id) {
+        id -> Integer,
+        csv_path -> Text,
+        modified_at -> BigInt,
+    }
+}
+
+table! {
+    puzzle_themes (puzzle_id, theme) {
+        puzzle_id -> Text,
+        theme -> Text,
+    }
+}
+
+table! {
+    puzzles (puzzle_id) {
+        puzzle_id -> Text,
+        fen -> Text,
+        moves -> Text,
+        rating -> Integer,
+        rd -> Integer,
+        popularity -> Integer,
+        nb_plays -> Integer,
+        themes -> Text,
+        game_url -> Text,
+        opening_tags -> Text,
+    }
+}
+
+table! {
+    ratings (id) {
+        id -> Integer,
+        rated_at -> BigInt,
+        puzzle_id -> Text,
+        rating -> Double,
+        deviation -> Double,
+        volatility -> Double,
+        profile_id -> Integer,
+    }
+}
+
+table! {
+    reviews (profile_id, puzzle_id) {
+        profile_id -> Integer,
+        puzzle_id -> Text,
+        fen -> Text,
+        moves -> Text,
+        rating -> Integer,
+        rd -> Integer,
+        popularity -> Integer,
+        nb_plays -> Integer,
+        themes -> Text,
+        game_url -> Text,
+        opening_tags -> Text,
+        ease -> Double,
+        interval_days -> Integer,
+        repetitions -> Integer,
+        due_at -> BigInt,
+    }
+}
+
+table! {
+    rush_runs (id) {
+        id -> Integer,
+        finished_at -> BigInt,
+        minutes -> Integer,
+        score -> Integer,
+        profile_id -> Integer,
+    }
+}
+
+table! {
+    saved_searches (id) {
+        id -> Integer,
+        profile_id -> Integer,
+        name -> Text,
+        preset -> Text,
+    }
+}
+
+table! {
+    streaks (id) {
+        id -> Integer,
+        finished_at -> BigInt,
+        length -> Integer,
+        profile_id -> Integer,
+    }
+}
+
+allow_tables_to_appear_in_same_query!(
+    attempts,
+    favs,
+    profiles,
+    puzzle_openings,
+    puzzle_source,
+    puzzle_themes,
+    puzzles,
+    ratings,
+    reviews,
+    rush_runs,
+    saved_searches,
+    streaks,
+);
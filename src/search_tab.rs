@@ -1,28 +1,71 @@
-use iced::widget::{Container, Button, column as col, Text, Radio, row, Row, Svg, PickList, Slider, Scrollable, Space};
+use iced::widget::{Container, Button, column as col, Text, TextInput, Radio, row, Row, Svg, PickList, Slider, Scrollable, Space};
 use iced::widget::text::LineHeight;
-use iced::{alignment, Command, Element, Alignment, Length};
+use iced::{alignment, Command, Element, Alignment, Length, Subscription, subscription};
+use iced::futures::SinkExt;
 use std::io::BufReader;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::mpsc;
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rand::rngs::StdRng;
 
 use iced_aw::TabLabel;
-use chess::Piece;
+use chess::{Color, Piece};
 use crate::config::load_config;
 use crate::styles::PieceTheme;
-use crate::{Tab, Message, config, styles, lang, db, openings};
+use crate::{Tab, Message, config, styles, lang, db, openings, eco};
+use crate::pattern::PositionPattern;
+use crate::dump::PuzzleDump;
+use crate::rush::{PuzzleRun, RunKind, RUSH_LENGTHS};
 
 use lang::{DisplayTranslated,PickListWrapper};
-use openings::{Openings, Variation};
+use openings::{Openings, Variation, OpeningCatalog};
 
 #[derive(Debug, Clone)]
 pub enum SearchMesssage {
     SliderMinRatingChanged(i32),
     SliderMaxRatingChanged(i32),
+    SliderMinPopularityChanged(i32),
+    SliderMaxPopularityChanged(i32),
+    SliderMinRdChanged(i32),
+    SliderMaxRdChanged(i32),
+    SliderMinMovesChanged(i32),
+    SliderMaxMovesChanged(i32),
+    ChangeMinPlays(String),
+    ChangeMaxPlays(String),
+    ChangePositionPattern(String),
+    ChangeOpeningLine(String),
     SelectTheme(PickListWrapper<TaticsThemes>),
+    SelectExtraTheme(PickListWrapper<TaticsThemes>),
+    AddThemeCondition(ThemeCondition),
+    RemoveThemeCondition(ThemeCondition, TaticsThemes),
     SelectOpening(PickListWrapper<Openings>),
     SelectVariation(PickListWrapper<Variation>),
+    OpeningsProgressed(u64),
+    OpeningsLoaded(OpeningCatalog),
     SelectOpeningSide(OpeningSide),
     SelectPiecePromotion(Piece),
+    ChangeSeed(String),
+    SelectOrder(PickListWrapper<PuzzleOrder>),
+    SelectPreset(String),
+    ChangePresetName(String),
+    SavePreset,
+    RenamePreset,
+    DeletePreset,
+    ChangeIdList(String),
+    LoadReviews,
+    LoadIdList,
+    IdListResolved(Vec<config::Puzzle>, Vec<String>),
     ClickSearch,
+    SearchProgressed(SearchProgress),
+    CancelSearch,
+    SearchCancelled(Vec<config::Puzzle>),
+    LoadPartialResults,
     SelectBase(SearchBase),
+    StartRun(RunKind),
+    SliderLevelWindowChanged(i32),
 }
 
 impl PickListWrapper<TaticsThemes> {
@@ -88,6 +131,11 @@ impl TaticsThemes {
         TaticsThemes::Master, TaticsThemes::MasterVsMaster, TaticsThemes::SuperGM
     ];
 
+    // The theme of a tag from the "themes" field of the puzzles
+    pub fn from_tag(tag: &str) -> Option<TaticsThemes> {
+        TaticsThemes::ALL.iter().skip(1).find(|theme| theme.get_tag_name() == tag).copied()
+    }
+
     pub fn get_tr_key(&self) -> &str {
         match self {
             TaticsThemes::All => "themes_all",
@@ -181,26 +229,388 @@ impl Default for TaticsThemes {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
+pub enum ThemeCondition {
+    AllOf, AnyOf, NoneOf
+}
+
+impl ThemeCondition {
+    pub fn get_tr_key(&self) -> &str {
+        match self {
+            ThemeCondition::AllOf => "theme_all_of",
+            ThemeCondition::AnyOf => "theme_any_of",
+            ThemeCondition::NoneOf => "theme_none_of",
+        }
+    }
+}
+
+// Themes combined with the one selected in the main theme pick list,
+// e.g. "fork AND endgame AND NOT mateIn1".
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct ThemeFilter {
+    pub all_of: Vec<TaticsThemes>,
+    pub any_of: Vec<TaticsThemes>,
+    pub none_of: Vec<TaticsThemes>,
+}
+
+impl ThemeFilter {
+    pub fn get(&self, condition: ThemeCondition) -> &Vec<TaticsThemes> {
+        match condition {
+            ThemeCondition::AllOf => &self.all_of,
+            ThemeCondition::AnyOf => &self.any_of,
+            ThemeCondition::NoneOf => &self.none_of,
+        }
+    }
+
+    fn get_mut(&mut self, condition: ThemeCondition) -> &mut Vec<TaticsThemes> {
+        match condition {
+            ThemeCondition::AllOf => &mut self.all_of,
+            ThemeCondition::AnyOf => &mut self.any_of,
+            ThemeCondition::NoneOf => &mut self.none_of,
+        }
+    }
+
+    pub fn add(&mut self, condition: ThemeCondition, theme: TaticsThemes) {
+        let themes = self.get_mut(condition);
+        if theme != TaticsThemes::All && !themes.contains(&theme) {
+            themes.push(theme);
+        }
+    }
+
+    pub fn remove(&mut self, condition: ThemeCondition, theme: TaticsThemes) {
+        self.get_mut(condition).retain(|item| *item != theme);
+    }
+
+    // The Themes field is a list of tags separated by spaces, we compare
+    // whole tags so "mateIn1" isn't found inside a longer tag.
+    pub fn matches(&self, themes: &str) -> bool {
+        let tags: Vec<&str> = themes.split_whitespace().collect();
+        let has_theme = |theme: &TaticsThemes| tags.iter().any(|tag| *tag == theme.get_tag_name());
+        self.all_of.iter().all(has_theme) &&
+            (self.any_of.is_empty() || self.any_of.iter().any(has_theme)) &&
+            !self.none_of.iter().any(has_theme)
+    }
+}
+
+// Ranges for the lichess statistics of the puzzles, so unreliable ones
+// (high rating deviation, few plays or disliked) can be left out.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct StatsFilter {
+    pub min_popularity: i32,
+    pub max_popularity: i32,
+    pub min_rd: i32,
+    pub max_rd: i32,
+    pub min_plays: i32,
+    pub max_plays: Option<i32>,
+}
+
+impl StatsFilter {
+    // The rating deviation slider goes up to this value, which means "no limit"
+    pub const MAX_RD: i32 = 500;
+
+    pub fn matches(&self, puzzle: &config::Puzzle) -> bool {
+        puzzle.popularity >= self.min_popularity && puzzle.popularity <= self.max_popularity &&
+            puzzle.rating_deviation >= self.min_rd &&
+            (self.max_rd >= StatsFilter::MAX_RD || puzzle.rating_deviation <= self.max_rd) &&
+            puzzle.nb_plays >= self.min_plays &&
+            self.max_plays.map_or(true, |max_plays| puzzle.nb_plays <= max_plays)
+    }
+}
+
+impl Default for StatsFilter {
+    fn default() -> StatsFilter {
+        StatsFilter {
+            min_popularity: -100,
+            max_popularity: 100,
+            min_rd: 0,
+            max_rd: StatsFilter::MAX_RD,
+            min_plays: 0,
+            max_plays: None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum OpeningSide {
     Any, White, Black
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PuzzleOrder {
+    #[default]
+    Random,
+    RatingAscending,
+    RatingDescending,
+    Popularity,
+    FileOrder,
+}
+
+impl PuzzleOrder {
+    const ALL: [PuzzleOrder; 5] = [
+        PuzzleOrder::Random, PuzzleOrder::RatingAscending, PuzzleOrder::RatingDescending,
+        PuzzleOrder::Popularity, PuzzleOrder::FileOrder
+    ];
+
+    pub fn get_tr_key(&self) -> &str {
+        match self {
+            PuzzleOrder::Random => "order_random",
+            PuzzleOrder::RatingAscending => "order_rating_asc",
+            PuzzleOrder::RatingDescending => "order_rating_desc",
+            PuzzleOrder::Popularity => "order_popularity",
+            PuzzleOrder::FileOrder => "order_file",
+        }
+    }
+
+    // The puzzles are expected in file order, the seed is only used for the random order
+    pub fn apply(&self, puzzles: &mut [config::Puzzle], seed: u64) {
+        match self {
+            PuzzleOrder::Random => puzzles.shuffle(&mut StdRng::seed_from_u64(seed)),
+            PuzzleOrder::RatingAscending => puzzles.sort_by_key(|puzzle| puzzle.rating),
+            PuzzleOrder::RatingDescending => puzzles.sort_by_key(|puzzle| std::cmp::Reverse(puzzle.rating)),
+            PuzzleOrder::Popularity => puzzles.sort_by_key(|puzzle| std::cmp::Reverse(puzzle.popularity)),
+            PuzzleOrder::FileOrder => (),
+        }
+    }
+}
+
+impl DisplayTranslated for PuzzleOrder {
+    fn to_str_tr(&self) -> &str {
+        self.get_tr_key()
+    }
+}
+
+impl PickListWrapper<PuzzleOrder> {
+    pub fn get_orders(lang: lang::Language) -> Vec<PickListWrapper<PuzzleOrder>> {
+        PuzzleOrder::ALL.iter().map(|order| PickListWrapper::<PuzzleOrder> { lang, item: *order }).collect()
+    }
+
+    pub fn new_order(lang: lang::Language, order: PuzzleOrder) -> Self {
+        Self { lang, item: order }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum SearchBase {
-    Lichess, Favorites
+    Lichess, Favorites,
+    // The lichess puzzles around the user's own rating
+    AtMyLevel,
+}
+
+impl SearchBase {
+    // The range of the level window slider, in rating points to each side
+    pub const MIN_LEVEL_WINDOW: i32 = 50;
+    pub const MAX_LEVEL_WINDOW: i32 = 500;
+}
+
+// A named set of search options, to quickly switch between the usual searches
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchPreset {
+    pub name: String,
+    pub min_rating: i32,
+    pub max_rating: i32,
+    pub theme: TaticsThemes,
+    pub opening: Openings,
+    pub variation: Variation,
+    pub side: OpeningSide,
+    pub base: SearchBase,
+}
+
+// Everything a puzzle is checked against in a search, used as is when
+// reading the csv and to refine the results of the database queries.
+#[derive(Debug, Clone)]
+pub struct PuzzleFilter {
+    pub min_rating: i32,
+    pub max_rating: i32,
+    pub min_moves: i32,
+    pub max_moves: i32,
+    pub themes: ThemeFilter,
+    pub stats: StatsFilter,
+    pub opening_tag: Option<String>,
+    // Exactly one of these OpeningTags, from the opening moves or ECO codes
+    pub opening_line_tags: Option<Vec<String>>,
+    pub op_side: OpeningSide,
+    pub position: Option<PositionPattern>,
+}
+
+impl PuzzleFilter {
+    // The moves sliders go up to this value, which means "no limit"
+    pub const MAX_MOVES: i32 = 10;
+
+    pub fn matches(&self, puzzle: &config::Puzzle) -> bool {
+        let side_matches = match self.op_side {
+            OpeningSide::Any => true,
+            OpeningSide::White => puzzle.player_side() == Some(Color::White),
+            OpeningSide::Black => puzzle.player_side() == Some(Color::Black),
+        };
+        let player_moves = puzzle.player_moves() as i32;
+        puzzle.rating >= self.min_rating && puzzle.rating <= self.max_rating &&
+            player_moves >= self.min_moves &&
+            (self.max_moves >= PuzzleFilter::MAX_MOVES || player_moves <= self.max_moves) &&
+            self.themes.matches(&puzzle.themes) &&
+            self.stats.matches(puzzle) &&
+            self.opening_tag.as_ref().map_or(true, |opening_tag| puzzle.opening.split_whitespace().any(|tag| tag == opening_tag)) &&
+            self.opening_line_tags.as_ref().map_or(true, |line_tags| {
+                line_tags.iter().any(|tags| puzzle.opening.split_whitespace().eq(tags.split_whitespace()))
+            }) &&
+            side_matches &&
+            // Checked last since it's the only one that needs to set up the board
+            self.position.as_ref().map_or(true, |pattern| {
+                puzzle.start_position().is_some_and(|board| pattern.matches(&board))
+            })
+    }
+}
+
+// Report the progress of the search after this many puzzles are read
+const PROGRESS_INTERVAL: usize = 10000;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchProgress {
+    pub scanned: usize,
+    pub found: usize,
+    pub percent: u64,
+}
+
+// A search running in the background, it's kept in the search tab
+// while the subscription that runs it is active.
+#[derive(Debug, Clone)]
+pub struct SearchJob {
+    id: usize,
+    base: SearchBase,
+    filter: PuzzleFilter,
+    result_limit: usize,
+    seed: u64,
+    order: PuzzleOrder,
+    level_window: i32,
+    // When the puzzles are for a puzzle rush or a streak
+    pub run: Option<RunKind>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl SearchJob {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    // With the adaptive base the puzzles are kept within this distance
+    // of the user's rating while the session goes on (but not in a puzzle
+    // rush or a streak, which go from easy to hard).
+    pub fn level_window(&self) -> Option<i32> {
+        if self.base == SearchBase::AtMyLevel && self.run.is_none() {
+            Some(self.level_window)
+        } else {
+            None
+        }
+    }
+}
+
+// Goes through the puzzles of a search, keeping the ones that match the filter
+pub struct SearchScan<'a> {
+    filter: &'a PuzzleFilter,
+    cancel: &'a AtomicBool,
+    report: &'a mut dyn FnMut(SearchProgress),
+    pub sampler: PuzzleSampler,
+    pub progress: SearchProgress,
+}
+
+impl<'a> SearchScan<'a> {
+    // Returns false when the search was cancelled and we should stop reading
+    pub fn check(&mut self, puzzle: config::Puzzle, percent: u64) -> bool {
+        self.progress.scanned += 1;
+        if self.filter.matches(&puzzle) {
+            self.progress.found += 1;
+            self.sampler.add(puzzle);
+        }
+        if self.progress.scanned % PROGRESS_INTERVAL == 0 {
+            self.progress.percent = percent;
+            (self.report)(self.progress);
+        }
+        !self.cancel.load(Ordering::Relaxed)
+    }
+
+    pub fn filter(&self) -> &'a PuzzleFilter {
+        self.filter
+    }
+}
+
+// Reservoir sampling, so when there are more matches than the results
+// limit we get a uniform sample of all of them instead of the first ones.
+pub struct PuzzleSampler {
+    limit: usize,
+    seen: usize,
+    rng: StdRng,
+    puzzles: Vec<config::Puzzle>,
+    // Position of each puzzle among the matches, to give them back in file order
+    positions: Vec<usize>,
+}
+
+impl PuzzleSampler {
+    pub fn new(limit: usize, seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        PuzzleSampler { limit, seen: 0, rng, puzzles: Vec::new(), positions: Vec::new() }
+    }
+
+    pub fn add(&mut self, puzzle: config::Puzzle) {
+        self.seen += 1;
+        if self.puzzles.len() < self.limit {
+            self.puzzles.push(puzzle);
+            self.positions.push(self.seen);
+        } else {
+            let index = self.rng.gen_range(0..self.seen);
+            if index < self.limit {
+                self.puzzles[index] = puzzle;
+                self.positions[index] = self.seen;
+            }
+        }
+    }
+
+    pub fn into_puzzles(self) -> Vec<config::Puzzle> {
+        let mut sample: Vec<(usize, config::Puzzle)> = self.positions.into_iter().zip(self.puzzles).collect();
+        sample.sort_by_key(|(position, _)| *position);
+        sample.into_iter().map(|(_, puzzle)| puzzle).collect()
+    }
 }
 
 #[derive(Debug)]
 pub struct SearchTab {
     pub theme: PickListWrapper<TaticsThemes>,
+    pub extra_theme: PickListWrapper<TaticsThemes>,
+    theme_filter: ThemeFilter,
     pub opening: PickListWrapper<Openings>,
     pub variation: PickListWrapper<Variation>,
+    opening_catalog: OpeningCatalog,
+    // How much of the csv was read while the catalog is (re)built, None once it's loaded
+    pub openings_progress: Option<u64>,
     pub opening_side: Option<OpeningSide>,
     slider_min_rating_value: i32,
     slider_max_rating_value: i32,
+    slider_min_moves_value: i32,
+    slider_max_moves_value: i32,
+    slider_level_window_value: i32,
+    stats_filter: StatsFilter,
+    min_plays_value: String,
+    max_plays_value: String,
+    position_pattern_value: String,
+    invalid_pattern: bool,
+    opening_line_value: String,
+    invalid_opening_line: bool,
     pub piece_theme_promotion: styles::PieceTheme,
     pub piece_to_promote_to: Piece,
+    seed_value: String,
+    last_seed: Option<u64>,
+    order: PickListWrapper<PuzzleOrder>,
+    search_count: usize,
+    pub search_job: Option<SearchJob>,
+    search_progress: SearchProgress,
+    partial_results: Option<Vec<config::Puzzle>>,
+    id_list_value: String,
+    id_list_status: String,
+    saved_searches: Vec<SearchPreset>,
+    selected_preset: Option<String>,
+    preset_name_value: String,
 
     pub show_searching_msg: bool,
     pub lang: lang::Language,
@@ -211,13 +621,39 @@ impl SearchTab {
     pub fn new() -> Self {
         SearchTab {
             theme : PickListWrapper::new_theme(config::SETTINGS.lang, config::SETTINGS.last_theme),
-            opening: PickListWrapper::new_opening(config::SETTINGS.lang, config::SETTINGS.last_opening),
+            extra_theme: PickListWrapper::new_theme(config::SETTINGS.lang, TaticsThemes::All),
+            theme_filter: config::SETTINGS.last_theme_filter.clone(),
+            opening: PickListWrapper::new_opening(config::SETTINGS.lang, config::SETTINGS.last_opening.clone()),
             variation: PickListWrapper::new_variation(config::SETTINGS.lang, config::SETTINGS.last_variation.clone()),
+            opening_catalog: OpeningCatalog::cached(),
+            openings_progress: Some(0),
             opening_side: config::SETTINGS.last_opening_side,
             slider_min_rating_value: config::SETTINGS.last_min_rating,
             slider_max_rating_value: config::SETTINGS.last_max_rating,
+            slider_min_moves_value: config::SETTINGS.last_min_moves,
+            slider_max_moves_value: config::SETTINGS.last_max_moves,
+            slider_level_window_value: config::SETTINGS.last_level_window,
+            stats_filter: config::SETTINGS.last_stats_filter,
+            min_plays_value: config::SETTINGS.last_stats_filter.min_plays.to_string(),
+            max_plays_value: config::SETTINGS.last_stats_filter.max_plays.map_or(String::new(), |max_plays| max_plays.to_string()),
+            position_pattern_value: config::SETTINGS.last_position_pattern.clone(),
+            invalid_pattern: false,
+            opening_line_value: config::SETTINGS.last_opening_line.clone(),
+            invalid_opening_line: false,
             piece_theme_promotion: config::SETTINGS.piece_theme,
             piece_to_promote_to: Piece::Queen,
+            seed_value: String::new(),
+            last_seed: None,
+            order: PickListWrapper::new_order(config::SETTINGS.lang, config::SETTINGS.last_order),
+            search_count: 0,
+            search_job: None,
+            search_progress: SearchProgress::default(),
+            partial_results: None,
+            id_list_value: String::new(),
+            id_list_status: String::new(),
+            saved_searches: SearchTab::profile_presets(),
+            selected_preset: None,
+            preset_name_value: String::new(),
             show_searching_msg: false,
             lang: config::SETTINGS.lang,
             base: Some(SearchBase::Lichess),
@@ -232,13 +668,76 @@ impl SearchTab {
             } SearchMesssage::SliderMaxRatingChanged(new_value) => {
                 self.slider_max_rating_value = new_value;
                 Command::none()
+            } SearchMesssage::SliderMinMovesChanged(new_value) => {
+                self.slider_min_moves_value = new_value;
+                Command::none()
+            } SearchMesssage::SliderMaxMovesChanged(new_value) => {
+                self.slider_max_moves_value = new_value;
+                Command::none()
+            } SearchMesssage::SliderLevelWindowChanged(new_value) => {
+                self.slider_level_window_value = new_value;
+                Command::none()
+            } SearchMesssage::SliderMinPopularityChanged(new_value) => {
+                self.stats_filter.min_popularity = new_value;
+                Command::none()
+            } SearchMesssage::SliderMaxPopularityChanged(new_value) => {
+                self.stats_filter.max_popularity = new_value;
+                Command::none()
+            } SearchMesssage::SliderMinRdChanged(new_value) => {
+                self.stats_filter.min_rd = new_value;
+                Command::none()
+            } SearchMesssage::SliderMaxRdChanged(new_value) => {
+                self.stats_filter.max_rd = new_value;
+                Command::none()
+            } SearchMesssage::ChangeMinPlays(value) => {
+                if value.is_empty() {
+                    self.stats_filter.min_plays = 0;
+                    self.min_plays_value = value;
+                } else if let Ok(min_plays) = value.parse::<i32>() {
+                    self.stats_filter.min_plays = min_plays;
+                    self.min_plays_value = value;
+                }
+                Command::none()
+            } SearchMesssage::ChangeMaxPlays(value) => {
+                if value.is_empty() {
+                    self.stats_filter.max_plays = None;
+                    self.max_plays_value = value;
+                } else if let Ok(max_plays) = value.parse::<i32>() {
+                    self.stats_filter.max_plays = Some(max_plays);
+                    self.max_plays_value = value;
+                }
+                Command::none()
+            } SearchMesssage::ChangePositionPattern(value) => {
+                self.position_pattern_value = value;
+                self.invalid_pattern = false;
+                Command::none()
+            } SearchMesssage::ChangeOpeningLine(value) => {
+                self.opening_line_value = value;
+                self.invalid_opening_line = false;
+                Command::none()
             } SearchMesssage::SelectTheme(new_theme) => {
                 self.theme = new_theme;
                 Command::none()
+            } SearchMesssage::SelectExtraTheme(new_theme) => {
+                self.extra_theme = new_theme;
+                Command::none()
+            } SearchMesssage::AddThemeCondition(condition) => {
+                self.theme_filter.add(condition, self.extra_theme.item);
+                Command::none()
+            } SearchMesssage::RemoveThemeCondition(condition, theme) => {
+                self.theme_filter.remove(condition, theme);
+                Command::none()
             } SearchMesssage::SelectOpening(new_opening) => {
                 self.opening = new_opening;
                 self.variation.item = Variation::ANY;
                 Command::none()
+            } SearchMesssage::OpeningsProgressed(percent) => {
+                self.openings_progress = Some(percent);
+                Command::none()
+            } SearchMesssage::OpeningsLoaded(catalog) => {
+                self.opening_catalog = catalog;
+                self.openings_progress = None;
+                Command::none()
             } SearchMesssage::SelectVariation(new_variation) => {
                 self.variation = new_variation;
                 Command::none()
@@ -248,25 +747,100 @@ impl SearchTab {
             } SearchMesssage::SelectPiecePromotion(piece) => {
                 self.piece_to_promote_to = piece;
                 Command::none()
-            } SearchMesssage::ClickSearch => {
-                self.show_searching_msg = true;
-                SearchTab::save_search_settings(self.slider_min_rating_value,
-                    self.slider_max_rating_value, self.theme.item,
-                    self.opening.item, self.variation.item.clone(), self.opening_side);
-
-                let config = load_config();
-                if self.base == Some(SearchBase::Favorites) {
-                    Command::perform(
-                        SearchTab::search_favs(self.slider_min_rating_value,
-                            self.slider_max_rating_value,
-                            self.theme.item, self.opening.item, self.variation.item.clone(),
-                            self.opening_side, config.search_results_limit), Message::LoadPuzzle)
+            } SearchMesssage::ChangeSeed(value) => {
+                if value.is_empty() || value.parse::<u64>().is_ok() {
+                    self.seed_value = value;
+                }
+                Command::none()
+            } SearchMesssage::SelectOrder(order) => {
+                self.order = order;
+                Command::none()
+            } SearchMesssage::SelectPreset(name) => {
+                if let Some(preset) = self.saved_searches.iter().find(|preset| preset.name == name).cloned() {
+                    self.apply_preset(&preset);
+                    self.preset_name_value = name.clone();
+                    self.selected_preset = Some(name);
+                }
+                Command::none()
+            } SearchMesssage::ChangePresetName(value) => {
+                self.preset_name_value = value;
+                Command::none()
+            } SearchMesssage::SavePreset => {
+                let name = self.preset_name_value.trim().to_string();
+                if !name.is_empty() {
+                    let preset = self.current_preset(name.clone());
+                    if let Some(existing) = self.saved_searches.iter_mut().find(|preset| preset.name == name) {
+                        *existing = preset;
+                    } else {
+                        self.saved_searches.push(preset);
+                    }
+                    self.selected_preset = Some(name);
+                    self.save_presets();
+                }
+                Command::none()
+            } SearchMesssage::RenamePreset => {
+                let new_name = self.preset_name_value.trim().to_string();
+                let name_taken = self.saved_searches.iter().any(|preset| preset.name == new_name);
+                if let Some(selected) = &self.selected_preset {
+                    if !new_name.is_empty() && !name_taken {
+                        if let Some(preset) = self.saved_searches.iter_mut().find(|preset| &preset.name == selected) {
+                            preset.name = new_name.clone();
+                            self.selected_preset = Some(new_name);
+                            self.save_presets();
+                        }
+                    }
+                }
+                Command::none()
+            } SearchMesssage::DeletePreset => {
+                if let Some(selected) = self.selected_preset.take() {
+                    self.saved_searches.retain(|preset| preset.name != selected);
+                    self.preset_name_value.clear();
+                    self.save_presets();
+                }
+                Command::none()
+            } SearchMesssage::ChangeIdList(value) => {
+                self.id_list_value = value;
+                Command::none()
+            } SearchMesssage::LoadReviews => {
+                Command::perform(SearchTab::load_reviews(), Message::LoadPuzzle)
+            } SearchMesssage::LoadIdList => {
+                let ids = SearchTab::parse_id_list(&self.id_list_value);
+                if ids.is_empty() {
+                    self.id_list_status = lang::tr(&self.lang, "no_ids");
+                    return Command::none();
+                }
+                self.id_list_status = lang::tr(&self.lang, "searching");
+                Command::perform(
+                    SearchTab::resolve_ids(ids),
+                    |(puzzles, unresolved)| Message::Search(SearchMesssage::IdListResolved(puzzles, unresolved)))
+            } SearchMesssage::IdListResolved(puzzles, unresolved) => {
+                self.id_list_status = if unresolved.is_empty() {
+                    String::new()
                 } else {
-                    Command::perform(
-                        SearchTab::search(self.slider_min_rating_value,
-                            self.slider_max_rating_value,
-                            self.theme.item, self.opening.item, self.variation.item.clone(),
-                            self.opening_side, config.search_results_limit), Message::LoadPuzzle)
+                    lang::tr(&self.lang, "unresolved_ids") + &unresolved.join(", ")
+                };
+                Command::perform(async move { Some(puzzles) }, Message::LoadPuzzle)
+            } SearchMesssage::ClickSearch => {
+                self.start_search(None)
+            } SearchMesssage::StartRun(kind) => {
+                self.start_search(Some(kind))
+            } SearchMesssage::SearchProgressed(progress) => {
+                self.search_progress = progress;
+                Command::none()
+            } SearchMesssage::CancelSearch => {
+                if let Some(job) = &self.search_job {
+                    job.cancel.store(true, Ordering::Relaxed);
+                }
+                Command::none()
+            } SearchMesssage::SearchCancelled(puzzles) => {
+                self.search_job = None;
+                self.show_searching_msg = false;
+                self.partial_results = Some(puzzles);
+                Command::none()
+            } SearchMesssage::LoadPartialResults => {
+                match self.partial_results.take() {
+                    Some(puzzles) => Command::perform(async move { Some(puzzles) }, Message::LoadPuzzle),
+                    None => Command::none(),
                 }
             } SearchMesssage::SelectBase(base) => {
                 self.base = Some(base);
@@ -275,17 +849,229 @@ impl SearchTab {
         }
     }
 
-    pub fn save_search_settings(min_rating: i32, max_rating: i32, theme: TaticsThemes, opening: Openings, variation: Variation, op_side: Option<OpeningSide>) {
+    // A puzzle rush or a streak uses the same filters, but takes puzzles
+    // of all ratings to go from easy to hard.
+    fn start_search(&mut self, run: Option<RunKind>) -> Command<Message> {
+        if self.search_job.is_some() {
+            return Command::none();
+        }
+        let mut filter = match self.puzzle_filter() {
+            Some(filter) => filter,
+            None => {
+                self.invalid_pattern = self.position_pattern().is_none();
+                self.invalid_opening_line = self.opening_line_tags().is_none();
+                return Command::none();
+            }
+        };
+        self.show_searching_msg = true;
+        self.save_search_settings();
+
+        if run.is_some() {
+            filter.min_rating = 0;
+            filter.max_rating = 3000;
+        }
+
+        let config = load_config();
+        // Without a seed we pick one, and show it so the same search can be repeated
+        let seed = self.seed_value.parse::<u64>().unwrap_or_else(|_| rand::thread_rng().gen());
+        self.last_seed = Some(seed);
+        self.search_count += 1;
+        self.search_progress = SearchProgress::default();
+        self.partial_results = None;
+        self.search_job = Some(SearchJob {
+            id: self.search_count,
+            base: self.base.unwrap_or(SearchBase::Lichess),
+            filter,
+            result_limit: config.search_results_limit,
+            seed,
+            order: self.order.item,
+            level_window: self.slider_level_window_value,
+            run,
+            cancel: Arc::new(AtomicBool::new(false)),
+        });
+        Command::none()
+    }
+
+    // Returns None if the position pattern is invalid, Some(None) if there's no pattern
+    fn position_pattern(&self) -> Option<Option<PositionPattern>> {
+        if self.position_pattern_value.trim().is_empty() {
+            Some(None)
+        } else {
+            PositionPattern::parse(&self.position_pattern_value).map(Some)
+        }
+    }
+
+    // Returns None if no opening we know matches the moves or ECO codes, Some(None) if they're empty
+    fn opening_line_tags(&self) -> Option<Option<Vec<String>>> {
+        if self.opening_line_value.trim().is_empty() {
+            Some(None)
+        } else {
+            eco::opening_tags(&self.opening_line_value).map(Some)
+        }
+    }
+
+    // Returns None if the position pattern or the opening line are invalid
+    pub fn puzzle_filter(&self) -> Option<PuzzleFilter> {
+        let position = self.position_pattern()?;
+        let opening_line_tags = self.opening_line_tags()?;
+        let mut themes = self.theme_filter.clone();
+        themes.add(ThemeCondition::AllOf, self.theme.item);
+        let opening_tag = if self.opening.item == Openings::ANY {
+            None
+        } else if self.variation.item.name != Variation::ANY_STR {
+            Some(self.variation.item.name.to_string())
+        } else {
+            Some(self.opening.item.get_field_name().to_string())
+        };
+        let op_side = self.opening_side.unwrap_or(OpeningSide::Any);
+        // Twice the window, so there's room to follow the rating as it changes
+        let (min_rating, max_rating) = if self.base == Some(SearchBase::AtMyLevel) {
+            let rating = db::current_rating().rating.round() as i32;
+            (rating - 2 * self.slider_level_window_value, rating + 2 * self.slider_level_window_value)
+        } else {
+            (self.slider_min_rating_value, self.slider_max_rating_value)
+        };
+        Some(PuzzleFilter {
+            min_rating,
+            max_rating,
+            min_moves: self.slider_min_moves_value,
+            max_moves: self.slider_max_moves_value,
+            themes,
+            stats: self.stats_filter,
+            opening_tag,
+            opening_line_tags,
+            op_side,
+            position,
+        })
+    }
+
+    fn current_preset(&self, name: String) -> SearchPreset {
+        SearchPreset {
+            name,
+            min_rating: self.slider_min_rating_value,
+            max_rating: self.slider_max_rating_value,
+            theme: self.theme.item,
+            opening: self.opening.item.clone(),
+            variation: self.variation.item.clone(),
+            side: self.opening_side.unwrap_or(OpeningSide::Any),
+            base: self.base.unwrap_or(SearchBase::Lichess),
+        }
+    }
+
+    fn apply_preset(&mut self, preset: &SearchPreset) {
+        self.slider_min_rating_value = preset.min_rating;
+        self.slider_max_rating_value = preset.max_rating;
+        self.theme = PickListWrapper::new_theme(self.lang, preset.theme);
+        self.opening = PickListWrapper::new_opening(self.lang, preset.opening.clone());
+        self.variation = PickListWrapper::new_variation(self.lang, preset.variation.clone());
+        self.opening_side = Some(preset.side);
+        self.base = Some(preset.base);
+    }
+
+    // The presets of the active profile. The ones saved in the settings file
+    // before there were profiles belong to the default profile.
+    fn profile_presets() -> Vec<SearchPreset> {
+        let presets = db::saved_searches();
+        if presets.is_empty() && db::active_profile() == db::DEFAULT_PROFILE {
+            load_config().saved_searches
+        } else {
+            presets
+        }
+    }
+
+    pub fn load_presets(&mut self) {
+        self.saved_searches = SearchTab::profile_presets();
+        self.selected_preset = None;
+        self.preset_name_value.clear();
+    }
+
+    fn save_presets(&self) {
+        db::save_searches(&self.saved_searches);
+        // Once they're in the database the ones in the settings file aren't needed anymore
+        let mut config = load_config();
+        if !config.saved_searches.is_empty() {
+            config.saved_searches.clear();
+            let file = std::fs::File::create("settings.json");
+            if let Ok(file) = file {
+                if serde_json::to_writer_pretty(file, &config).is_err() {
+                    println!("Error saving search presets.");
+                }
+            }
+        }
+    }
+
+    // The ids can be pasted (separated by spaces, commas or new lines, lichess training
+    // links work too) or be in a file, in which case we get the path to it. It's only
+    // taken as a path if it's a single word naming a file that exists, so a list of
+    // ids is never read from the disk.
+    fn parse_id_list(text: &str) -> Vec<String> {
+        let is_separator = |c: char| c.is_whitespace() || c == ',' || c == ';';
+        let text = text.trim();
+        let content = if !text.contains(is_separator) && std::path::Path::new(text).is_file() {
+            std::fs::read_to_string(text).unwrap_or_default()
+        } else {
+            text.to_string()
+        };
+        content.split(is_separator)
+            .map(|token| token.trim_end_matches('/').rsplit('/').next().unwrap_or_default())
+            .filter(|id| !id.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    // Returns the puzzles in the order of the ids, and the ids we couldn't find
+    pub async fn resolve_ids(ids: Vec<String>) -> (Vec<config::Puzzle>, Vec<String>) {
+        let mut found: HashMap<String, config::Puzzle> = db::find_puzzles(&ids).into_iter()
+            .map(|puzzle| (puzzle.puzzle_id.clone(), puzzle))
+            .collect();
+        let mut missing: HashSet<String> = ids.iter().filter(|id| !found.contains_key(*id)).cloned().collect();
+        if !missing.is_empty() && !db::has_imported_puzzles() {
+            if let Ok(mut dump) = PuzzleDump::open(&config::SETTINGS.puzzle_db_location) {
+                for puzzle in dump.reader.deserialize::<config::Puzzle>().flatten() {
+                    if missing.remove(&puzzle.puzzle_id) {
+                        found.insert(puzzle.puzzle_id.clone(), puzzle);
+                        if missing.is_empty() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        let mut puzzles = Vec::new();
+        let mut unresolved = Vec::new();
+        for id in ids {
+            match found.get(&id) {
+                Some(puzzle) => puzzles.push(puzzle.clone()),
+                None => unresolved.push(id),
+            }
+        }
+        (puzzles, unresolved)
+    }
+
+    pub async fn load_reviews() -> Option<Vec<config::Puzzle>> {
+        Some(db::due_reviews())
+    }
+
+
+    pub fn save_search_settings(&self) {
         let file = std::fs::File::open("settings.json");
         if let Ok(file) = file {
             let buf_reader = BufReader::new(file);
             if let Ok(mut config) = serde_json::from_reader::<std::io::BufReader<std::fs::File>, config::OfflinePuzzlesConfig>(buf_reader) {
-                config.last_min_rating = min_rating;
-                config.last_max_rating = max_rating;
-                config.last_theme = theme;
-                config.last_opening = opening;
-                config.last_variation = variation;
-                config.last_opening_side = op_side;
+                config.last_min_rating = self.slider_min_rating_value;
+                config.last_max_rating = self.slider_max_rating_value;
+                config.last_min_moves = self.slider_min_moves_value;
+                config.last_max_moves = self.slider_max_moves_value;
+                config.last_theme = self.theme.item;
+                config.last_theme_filter = self.theme_filter.clone();
+                config.last_stats_filter = self.stats_filter;
+                config.last_opening = self.opening.item.clone();
+                config.last_variation = self.variation.item.clone();
+                config.last_opening_side = self.opening_side;
+                config.last_position_pattern = self.position_pattern_value.clone();
+                config.last_opening_line = self.opening_line_value.clone();
+                config.last_order = self.order.item;
+                config.last_level_window = self.slider_level_window_value;
 
                 let file = std::fs::File::create("settings.json");
                 if let Ok(file) = file {
@@ -297,91 +1083,108 @@ impl SearchTab {
         }
     }
 
-    pub async fn search_favs(min_rating: i32, max_rating: i32, theme: TaticsThemes, opening: Openings, variation:Variation, op_side: Option<OpeningSide>, result_limit: usize) -> Option<Vec<config::Puzzle>> {
-        db::get_favorites(min_rating, max_rating, theme, opening, variation, op_side, result_limit)
-    }
-
-    pub async fn search(min_rating: i32, max_rating: i32, theme: TaticsThemes, opening: Openings, variation: Variation, op_side: Option<OpeningSide>, result_limit: usize) -> Option<Vec<config::Puzzle>> {
-        let mut puzzles: Vec<config::Puzzle> = Vec::new();
-
-        let reader = csv::ReaderBuilder::new()
-        .has_headers(false)
-        .flexible(true)
-        .from_path(&config::SETTINGS.puzzle_db_location);
+    // Runs the search in its own thread, sending the progress to the search tab
+    // until it finishes (loading the puzzles found) or is cancelled.
+    pub fn run_search(job: SearchJob) -> Subscription<Message> {
+        subscription::channel(
+            (std::any::TypeId::of::<SearchJob>(), job.id),
+            100,
+            move |mut output| {
+                let job = job.clone();
 
-        if let Ok(mut reader) = reader {
-            puzzles.clear();
-            //self.current_puzzle_move = 1;
-            //self.current_puzzle = 0;
-            if opening != Openings::Any {
-                let opening_tag: &str = if variation.name != Variation::ANY_STR {
-                    &variation.name
-                } else {
-                    opening.get_field_name()
-                };
-                let side = match op_side {
-                    None => OpeningSide::Any,
-                    Some(x) => x
-                };
-                match side {
-                    OpeningSide::Any => {
-                        for result in reader.deserialize::<config::Puzzle>() {
-                            if let Ok(record) = result {
-                                if record.opening.contains(opening_tag) &&
-                                        record.rating >= min_rating && record.rating <= max_rating &&
-                                        record.themes.contains(theme.get_tag_name()) {
-                                    puzzles.push(record);
-                                }
-                            }
-                            if puzzles.len() == result_limit {
-                                break;
+                async move {
+                    let (sender, mut receiver) = mpsc::channel(100);
+                    std::thread::spawn(move || {
+                        let progress_sender = sender.clone();
+                        let mut puzzles = SearchTab::search(&job, &mut |progress| {
+                            let _ = progress_sender.blocking_send(Message::Search(SearchMesssage::SearchProgressed(progress)));
+                        });
+                        if let Some(puzzles) = &mut puzzles {
+                            job.order.apply(puzzles, job.seed);
+                            if let Some(kind) = job.run {
+                                *puzzles = PuzzleRun::progressive(kind, std::mem::take(puzzles));
                             }
                         }
-                    } OpeningSide::Black => {
-                        for result in reader.deserialize::<config::Puzzle>() {
-                            if let Ok(record) = result {
-                                if record.opening.contains(opening_tag) &&
-                                        !record.game_url.contains("black") &&
-                                        record.rating >= min_rating && record.rating <= max_rating &&
-                                        record.themes.contains(theme.get_tag_name()) {
-                                    puzzles.push(record);
-                                }
-                            }
-                            if puzzles.len() == result_limit {
-                                break;
-                            }
+                        let msg = if job.cancel.load(Ordering::Relaxed) {
+                            Message::Search(SearchMesssage::SearchCancelled(puzzles.unwrap_or_default()))
+                        } else {
+                            Message::SearchFinished(job.id, puzzles)
+                        };
+                        let _ = sender.blocking_send(msg);
+                    });
+                    loop {
+                        match receiver.recv().await {
+                            Some(msg) => output.send(msg).await.expect("Error on the mpsc channel in the search subscription"),
+                            // Search done, we just wait for the subscription to be dropped
+                            None => tokio::time::sleep(std::time::Duration::from_millis(100)).await,
                         }
-                    } OpeningSide::White => {
-                        for result in reader.deserialize::<config::Puzzle>() {
-                            if let Ok(record) = result {
-                                if record.opening.contains(opening_tag) &&
-                                        record.game_url.contains("black") &&
-                                        record.rating >= min_rating && record.rating <= max_rating &&
-                                        record.themes.contains(theme.get_tag_name()) {
-                                    puzzles.push(record);
-                                }
-                            }
-                            if puzzles.len() == result_limit {
+                    }
+                }
+            }
+        )
+    }
+
+    // Builds the openings catalog in its own thread like the search, since
+    // without the cache it needs to read the whole csv.
+    pub fn load_openings() -> Subscription<Message> {
+        subscription::channel(
+            std::any::TypeId::of::<OpeningCatalog>(),
+            100,
+            |mut output| async move {
+                let (sender, mut receiver) = mpsc::channel(100);
+                std::thread::spawn(move || {
+                    let progress_sender = sender.clone();
+                    let catalog = OpeningCatalog::load(&mut |percent| {
+                        let _ = progress_sender.blocking_send(Message::Search(SearchMesssage::OpeningsProgressed(percent)));
+                    });
+                    let _ = sender.blocking_send(Message::Search(SearchMesssage::OpeningsLoaded(catalog)));
+                });
+                loop {
+                    match receiver.recv().await {
+                        Some(msg) => output.send(msg).await.expect("Error on the mpsc channel in the openings subscription"),
+                        // Catalog loaded, we just wait for the subscription to be dropped
+                        None => tokio::time::sleep(std::time::Duration::from_millis(100)).await,
+                    }
+                }
+            }
+        )
+    }
+
+    pub fn search(job: &SearchJob, report: &mut dyn FnMut(SearchProgress)) -> Option<Vec<config::Puzzle>> {
+        let mut scan = SearchScan {
+            filter: &job.filter,
+            cancel: &job.cancel,
+            report,
+            sampler: PuzzleSampler::new(job.result_limit, Some(job.seed)),
+            progress: SearchProgress::default(),
+        };
+        // Once the csv was imported there's no need to parse it again
+        if job.base == SearchBase::Favorites || db::has_imported_puzzles() {
+            db::search_puzzles(job.base, &mut scan).ok()?;
+            return Some(scan.sampler.into_puzzles());
+        }
+
+        if let Ok(mut dump) = PuzzleDump::open(&config::SETTINGS.puzzle_db_location) {
+            let mut record = csv::StringRecord::new();
+            loop {
+                match dump.reader.read_record(&mut record) {
+                    Ok(true) => {
+                        if let Ok(puzzle) = record.deserialize::<config::Puzzle>(None) {
+                            if !scan.check(puzzle, dump.percent_read()) {
                                 break;
                             }
                         }
-                    }
-                }
-            } else {
-                for result in reader.deserialize::<config::Puzzle>() {
-                    if let Ok(record) = result {
-                        if record.rating >= min_rating && record.rating <= max_rating &&
-                                record.themes.contains(theme.get_tag_name()) {
-                            puzzles.push(record);
+                    } Ok(false) => break,
+                    // Skip malformed lines, but give up if we can't read the file anymore
+                    Err(e) => {
+                        if e.is_io_error() {
+                            break;
                         }
                     }
-                    if puzzles.len() == result_limit {
-                        break;
-                    }
                 }
             }
         }
-        Some(puzzles)
+        Some(scan.sampler.into_puzzles())
     }
 
 }
@@ -399,58 +1202,205 @@ impl Tab for SearchTab {
     }
 
     fn content(&self) -> Element<Message, iced::Renderer<styles::Theme>> {
+        // The adaptive base takes the range from the user's rating
+        let rating_rows: Element<SearchMesssage, iced::Renderer<styles::Theme>> = if self.base == Some(SearchBase::AtMyLevel) {
+            row![
+                Text::new(lang::tr(&self.lang, "level_window")),
+                Slider::new(
+                    SearchBase::MIN_LEVEL_WINDOW..=SearchBase::MAX_LEVEL_WINDOW,
+                    self.slider_level_window_value,
+                    SearchMesssage::SliderLevelWindowChanged,
+                ).step(10),
+                Text::new(String::from("±") + &self.slider_level_window_value.to_string())
+            ].width(Length::Fill).into()
+        } else {
+            col![
+                row![
+                    Text::new(lang::tr(&self.lang, "min_rating")),
+                    Slider::new(
+                        0..=3000,
+                        self.slider_min_rating_value,
+                        SearchMesssage::SliderMinRatingChanged,
+                    ),
+                    Text::new(self.slider_min_rating_value.to_string())
+                ].width(Length::Fill),
+                row![
+                    Text::new(lang::tr(&self.lang, "max_rating")),
+                    Slider::new(
+                        0..=3000,
+                        self.slider_max_rating_value,
+                        SearchMesssage::SliderMaxRatingChanged,
+                    ),
+                    Text::new(self.slider_max_rating_value.to_string())
+                ].width(Length::Fill),
+            ].spacing(10).into()
+        };
         let mut search_col = col![
             Container::new(
                 row![
                     Radio::new(lang::tr(&self.lang, "lichess_db"), SearchBase::Lichess, self.base, SearchMesssage::SelectBase),
                     Radio::new(lang::tr(&self.lang, "my_favories"), SearchBase::Favorites, self.base, SearchMesssage::SelectBase),
+                    Radio::new(lang::tr(&self.lang, "at_my_level"), SearchBase::AtMyLevel, self.base, SearchMesssage::SelectBase),
                 ].spacing(10)
             ).align_x(alignment::Horizontal::Center).width(Length::Fill),
             row![
-                Text::new(lang::tr(&self.lang, "min_rating")),
+                Text::new(lang::tr(&self.lang, "saved_searches")),
+                PickList::new(
+                    self.saved_searches.iter().map(|preset| preset.name.clone()).collect::<Vec<String>>(),
+                    self.selected_preset.clone(),
+                    SearchMesssage::SelectPreset
+                ),
+            ].spacing(5).align_items(Alignment::Center),
+            row![
+                TextInput::new(&lang::tr(&self.lang, "preset_name"), &self.preset_name_value)
+                    .on_input(SearchMesssage::ChangePresetName)
+                    .width(150),
+                Button::new(Text::new(lang::tr(&self.lang, "save_preset"))).padding(5).on_press(SearchMesssage::SavePreset),
+                Button::new(Text::new(lang::tr(&self.lang, "rename_preset"))).padding(5).on_press(SearchMesssage::RenamePreset),
+                Button::new(Text::new(lang::tr(&self.lang, "delete_preset"))).padding(5).on_press(SearchMesssage::DeletePreset),
+            ].spacing(5).align_items(Alignment::Center),
+            rating_rows,
+            row![
+                Text::new(lang::tr(&self.lang, "min_moves")),
                 Slider::new(
-                    0..=3000,
-                    self.slider_min_rating_value,
-                    SearchMesssage::SliderMinRatingChanged,
+                    1..=PuzzleFilter::MAX_MOVES,
+                    self.slider_min_moves_value,
+                    SearchMesssage::SliderMinMovesChanged,
                 ),
-                Text::new(self.slider_min_rating_value.to_string())
+                Text::new(self.slider_min_moves_value.to_string())
             ].width(Length::Fill),
             row![
-                Text::new(lang::tr(&self.lang, "max_rating")),
+                Text::new(lang::tr(&self.lang, "max_moves")),
                 Slider::new(
-                    0..=3000,
-                    self.slider_max_rating_value,
-                    SearchMesssage::SliderMaxRatingChanged,
+                    1..=PuzzleFilter::MAX_MOVES,
+                    self.slider_max_moves_value,
+                    SearchMesssage::SliderMaxMovesChanged,
                 ),
-                Text::new(self.slider_max_rating_value.to_string())
-                ].width(Length::Fill),
+                Text::new(if self.slider_max_moves_value >= PuzzleFilter::MAX_MOVES {
+                    lang::tr(&self.lang, "no_limit")
+                } else {
+                    self.slider_max_moves_value.to_string()
+                })
+            ].width(Length::Fill),
+            row![
+                Text::new(lang::tr(&self.lang, "min_popularity")),
+                Slider::new(
+                    -100..=100,
+                    self.stats_filter.min_popularity,
+                    SearchMesssage::SliderMinPopularityChanged,
+                ),
+                Text::new(self.stats_filter.min_popularity.to_string())
+            ].width(Length::Fill),
+            row![
+                Text::new(lang::tr(&self.lang, "max_popularity")),
+                Slider::new(
+                    -100..=100,
+                    self.stats_filter.max_popularity,
+                    SearchMesssage::SliderMaxPopularityChanged,
+                ),
+                Text::new(self.stats_filter.max_popularity.to_string())
+            ].width(Length::Fill),
+            row![
+                Text::new(lang::tr(&self.lang, "min_rd")),
+                Slider::new(
+                    0..=StatsFilter::MAX_RD,
+                    self.stats_filter.min_rd,
+                    SearchMesssage::SliderMinRdChanged,
+                ),
+                Text::new(self.stats_filter.min_rd.to_string())
+            ].width(Length::Fill),
+            row![
+                Text::new(lang::tr(&self.lang, "max_rd")),
+                Slider::new(
+                    0..=StatsFilter::MAX_RD,
+                    self.stats_filter.max_rd,
+                    SearchMesssage::SliderMaxRdChanged,
+                ),
+                Text::new(if self.stats_filter.max_rd >= StatsFilter::MAX_RD {
+                    lang::tr(&self.lang, "no_limit")
+                } else {
+                    self.stats_filter.max_rd.to_string()
+                })
+            ].width(Length::Fill),
+            row![
+                Text::new(lang::tr(&self.lang, "times_played_range")),
+                TextInput::new("0", &self.min_plays_value).on_input(SearchMesssage::ChangeMinPlays).width(80),
+                Text::new("-"),
+                TextInput::new(&lang::tr(&self.lang, "no_limit"), &self.max_plays_value).on_input(SearchMesssage::ChangeMaxPlays).width(80),
+            ].spacing(5).align_items(Alignment::Center),
             Text::new(lang::tr(&self.lang, "theme_label")),
             PickList::new(
                 PickListWrapper::get_themes(self.lang.clone()),
                 Some(self.theme.clone()),
                 SearchMesssage::SelectTheme
             ),
+            Text::new(lang::tr(&self.lang, "more_themes")),
+            row![
+                PickList::new(
+                    PickListWrapper::get_themes(self.lang),
+                    Some(self.extra_theme.clone()),
+                    SearchMesssage::SelectExtraTheme
+                ),
+                Button::new(Text::new(lang::tr(&self.lang, "theme_all_of"))).padding(5).on_press(SearchMesssage::AddThemeCondition(ThemeCondition::AllOf)),
+                Button::new(Text::new(lang::tr(&self.lang, "theme_any_of"))).padding(5).on_press(SearchMesssage::AddThemeCondition(ThemeCondition::AnyOf)),
+                Button::new(Text::new(lang::tr(&self.lang, "theme_none_of"))).padding(5).on_press(SearchMesssage::AddThemeCondition(ThemeCondition::NoneOf)),
+            ].spacing(5).align_items(Alignment::Center),
+        ].spacing(10).align_items(Alignment::Center);
+
+        for condition in [ThemeCondition::AllOf, ThemeCondition::AnyOf, ThemeCondition::NoneOf] {
+            let themes = self.theme_filter.get(condition);
+            if !themes.is_empty() {
+                let mut row_condition = Row::new().spacing(5).align_items(Alignment::Center)
+                    .push(Text::new(lang::tr(&self.lang, condition.get_tr_key()) + ":"));
+                for theme in themes {
+                    row_condition = row_condition.push(
+                        Button::new(Text::new(lang::tr(&self.lang, theme.get_tr_key()) + " x"))
+                            .padding(3)
+                            .on_press(SearchMesssage::RemoveThemeCondition(condition, *theme))
+                    );
+                }
+                search_col = search_col.push(row_condition);
+            }
+        }
+
+        search_col = search_col.push(col![
             Text::new(lang::tr(&self.lang, "in_opening")),
             PickList::new(
-                PickListWrapper::get_openings(self.lang.clone()),
+                PickListWrapper::get_openings(self.lang, &self.opening_catalog),
                 Some(self.opening.clone()),
                 SearchMesssage::SelectOpening
             ),
             Text::new(lang::tr(&self.lang, "in_the_variation")),
             PickList::new(
-                PickListWrapper::get_variations(self.lang.clone(), Some(&self.opening.item)),
+                PickListWrapper::get_variations(self.lang, &self.opening_catalog, &self.opening.item),
                 Some(self.variation.clone()),
                 SearchMesssage::SelectVariation
             )
-        ].spacing(10).align_items(Alignment::Center);
+        ].spacing(10).align_items(Alignment::Center));
+        if let Some(percent) = self.openings_progress {
+            search_col = search_col.push(Text::new(lang::tr(&self.lang, "loading_openings") + &percent.to_string() + "%"));
+        }
 
-        if self.opening.item != Openings::Any {
-            let row_color = row![
-                Radio::new(lang::tr(&self.lang, "any"), OpeningSide::Any, self.opening_side, SearchMesssage::SelectOpeningSide),
-                Radio::new(lang::tr(&self.lang, "white"), OpeningSide::White, self.opening_side, SearchMesssage::SelectOpeningSide),
-                Radio::new(lang::tr(&self.lang, "black"), OpeningSide::Black, self.opening_side, SearchMesssage::SelectOpeningSide)
-            ].spacing(5).align_items(Alignment::Center);
-            search_col = search_col.push(Text::new(lang::tr(&self.lang, "side"))).push(row_color);
+        search_col = search_col
+            .push(Text::new(lang::tr(&self.lang, "opening_line")))
+            .push(TextInput::new("1.e4 c5 2.Nf3 d6 / B90-B99", &self.opening_line_value).on_input(SearchMesssage::ChangeOpeningLine));
+        if self.invalid_opening_line {
+            search_col = search_col.push(Text::new(lang::tr(&self.lang, "unknown_opening_line")));
+        }
+
+        let row_color = row![
+            Radio::new(lang::tr(&self.lang, "any"), OpeningSide::Any, self.opening_side, SearchMesssage::SelectOpeningSide),
+            Radio::new(lang::tr(&self.lang, "white"), OpeningSide::White, self.opening_side, SearchMesssage::SelectOpeningSide),
+            Radio::new(lang::tr(&self.lang, "black"), OpeningSide::Black, self.opening_side, SearchMesssage::SelectOpeningSide)
+        ].spacing(5).align_items(Alignment::Center);
+        search_col = search_col.push(Text::new(lang::tr(&self.lang, "side"))).push(row_color);
+
+        search_col = search_col
+            .push(Text::new(lang::tr(&self.lang, "position_pattern")))
+            .push(TextInput::new("", &self.position_pattern_value).on_input(SearchMesssage::ChangePositionPattern))
+            .push(Text::new(lang::tr(&self.lang, "position_pattern_help")).size(14));
+        if self.invalid_pattern {
+            search_col = search_col.push(Text::new(lang::tr(&self.lang, "invalid_pattern")));
         }
 
         let mut row_promotion = Row::new().spacing(5).align_items(Alignment::Center);
@@ -531,12 +1481,75 @@ impl Tab for SearchTab {
             }
         }
 
+        search_col = search_col.push(
+            row![
+                Text::new(lang::tr(&self.lang, "seed")),
+                TextInput::new("", &self.seed_value).on_input(SearchMesssage::ChangeSeed).width(200),
+            ].spacing(5).align_items(Alignment::Center)
+        );
+        if let Some(last_seed) = self.last_seed {
+            search_col = search_col.push(Text::new(lang::tr(&self.lang, "last_seed") + &last_seed.to_string()).size(14));
+        }
+        search_col = search_col.push(
+            row![
+                Text::new(lang::tr(&self.lang, "puzzle_order")),
+                PickList::new(
+                    PickListWrapper::get_orders(self.lang),
+                    Some(self.order.clone()),
+                    SearchMesssage::SelectOrder
+                ),
+            ].spacing(5).align_items(Alignment::Center)
+        );
+
         search_col = search_col.push(Space::new(Length::Fill, 10));
         if self.show_searching_msg {
-            search_col = search_col.push(Text::new(lang::tr(&self.lang, "searching")));
+            search_col = search_col
+                .push(Text::new(lang::tr(&self.lang, "searching")))
+                .push(Text::new(
+                    lang::tr(&self.lang, "puzzles_read") + &self.search_progress.scanned.to_string() + ", " +
+                    &lang::tr(&self.lang, "puzzles_found") + &self.search_progress.found.to_string() +
+                    " (" + &self.search_progress.percent.to_string() + "%)"))
+                .push(Button::new(Text::new(lang::tr(&self.lang, "cancel_search"))).padding(5).on_press(SearchMesssage::CancelSearch));
+        } else {
+            search_col = search_col.push(Button::new(Text::new(lang::tr(&self.lang, "btn_search"))).padding(5).on_press(SearchMesssage::ClickSearch));
+        }
+        search_col = search_col
+            .push(Text::new(lang::tr(&self.lang, "puzzle_id_list")))
+            .push(
+                row![
+                    TextInput::new("", &self.id_list_value).on_input(SearchMesssage::ChangeIdList).width(250),
+                    Button::new(Text::new(lang::tr(&self.lang, "load_id_list"))).padding(5).on_press(SearchMesssage::LoadIdList),
+                ].spacing(5).align_items(Alignment::Center)
+            );
+        if !self.id_list_status.is_empty() {
+            search_col = search_col.push(Text::new(&self.id_list_status));
+        }
+        search_col = search_col.push(Button::new(Text::new(lang::tr(&self.lang, "review_due"))).padding(5).on_press(SearchMesssage::LoadReviews));
+        let mut rush_row = row![Text::new(lang::tr(&self.lang, "puzzle_rush"))].spacing(5).align_items(Alignment::Center);
+        for minutes in RUSH_LENGTHS {
+            let button = Button::new(Text::new(minutes.to_string() + " min")).padding(5);
+            rush_row = rush_row.push(if self.show_searching_msg {
+                button
+            } else {
+                button.on_press(SearchMesssage::StartRun(RunKind::Rush(minutes)))
+            });
+        }
+        let streak_button = Button::new(Text::new(lang::tr(&self.lang, "start_streak"))).padding(5);
+        rush_row = rush_row.push(if self.show_searching_msg {
+            streak_button
+        } else {
+            streak_button.on_press(SearchMesssage::StartRun(RunKind::Streak))
+        });
+        search_col = search_col.push(rush_row);
+        if let Some(partial_results) = &self.partial_results {
+            search_col = search_col.push(
+                row![
+                    Text::new(lang::tr(&self.lang, "search_cancelled") + &partial_results.len().to_string()),
+                    Button::new(Text::new(lang::tr(&self.lang, "load_partial_results"))).padding(5).on_press(SearchMesssage::LoadPartialResults),
+                ].spacing(5).align_items(Alignment::Center)
+            );
         }
         search_col = search_col
-            .push(Button::new(Text::new(lang::tr(&self.lang, "btn_search"))).padding(5).on_press(SearchMesssage::ClickSearch))
             .push(Text::new(lang::tr(&self.lang, "promotion_piece")))
             .push(row_promotion);
 
@@ -548,3 +1561,106 @@ impl Tab for SearchTab {
         content.map(Message::Search)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle(id: &str) -> config::Puzzle {
+        config::Puzzle {
+            puzzle_id: id.to_string(),
+            fen: String::from("q3k1nr/1pp1nQpp/3p4/1P2p3/4P3/B1PP1b2/B5PP/5K2 b k - 0 17"),
+            moves: String::from("e8d7 a2e6 d7d8 f7f8"),
+            rating: 1760,
+            rating_deviation: 80,
+            popularity: 83,
+            nb_plays: 72,
+            themes: String::from("mate mateIn2 middlegame short"),
+            game_url: String::from("https://lichess.org/yyznGmXs/black#34"),
+            opening: String::from("Italian_Game Italian_Game_Classical_Variation"),
+        }
+    }
+
+    fn any_puzzle() -> PuzzleFilter {
+        PuzzleFilter {
+            min_rating: 0,
+            max_rating: 3000,
+            min_moves: 1,
+            max_moves: PuzzleFilter::MAX_MOVES,
+            themes: ThemeFilter::default(),
+            stats: StatsFilter::default(),
+            opening_tag: None,
+            opening_line_tags: None,
+            op_side: OpeningSide::Any,
+            position: None,
+        }
+    }
+
+    #[test]
+    fn pasted_id_lists() {
+        let ids = vec!["00sHx", "00sJ9", "01Kbd"];
+        assert_eq!(SearchTab::parse_id_list("00sHx,00sJ9, 01Kbd"), ids);
+        assert_eq!(SearchTab::parse_id_list("00sHx\n00sJ9\r\n\n01Kbd\n"), ids);
+        assert_eq!(SearchTab::parse_id_list("https://lichess.org/training/00sHx; 00sJ9 lichess.org/training/01Kbd/"), ids);
+        assert_eq!(SearchTab::parse_id_list("00sHx"), vec!["00sHx"]);
+        assert!(SearchTab::parse_id_list("  ").is_empty());
+    }
+
+    #[test]
+    fn id_list_from_a_file() {
+        let path = std::env::temp_dir().join(format!("ocp-ids-{}.txt", std::process::id()));
+        std::fs::write(&path, "00sHx\n00sJ9\n").unwrap();
+        assert_eq!(SearchTab::parse_id_list(path.to_str().unwrap()), vec!["00sHx", "00sJ9"]);
+        // With more than one word it's a list, even if the first one is a file
+        let ids = SearchTab::parse_id_list(&(String::from(path.to_str().unwrap()) + ",01Kbd"));
+        assert_eq!(ids.last().map(String::as_str), Some("01Kbd"));
+        assert!(!ids.iter().any(|id| id == "00sHx"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn filter_matches() {
+        let puzzle = puzzle("00sHx");
+        assert!(any_puzzle().matches(&puzzle));
+        assert!(!PuzzleFilter { max_rating: 1700, ..any_puzzle() }.matches(&puzzle));
+        assert!(!PuzzleFilter { max_moves: 1, ..any_puzzle() }.matches(&puzzle));
+        // Black moved last in the FEN, the player is white
+        assert!(PuzzleFilter { op_side: OpeningSide::White, ..any_puzzle() }.matches(&puzzle));
+        assert!(!PuzzleFilter { op_side: OpeningSide::Black, ..any_puzzle() }.matches(&puzzle));
+        assert!(PuzzleFilter { opening_tag: Some(String::from("Italian_Game")), ..any_puzzle() }.matches(&puzzle));
+        assert!(!PuzzleFilter { opening_tag: Some(String::from("Sicilian_Defense")), ..any_puzzle() }.matches(&puzzle));
+
+        let mut themes = ThemeFilter::default();
+        themes.all_of.push(TaticsThemes::MateIn2);
+        assert!(PuzzleFilter { themes: themes.clone(), ..any_puzzle() }.matches(&puzzle));
+        themes.none_of.push(TaticsThemes::Mate);
+        assert!(!PuzzleFilter { themes, ..any_puzzle() }.matches(&puzzle));
+    }
+
+    #[test]
+    fn sampler_keeps_everything_under_the_limit() {
+        let mut sampler = PuzzleSampler::new(10, Some(1));
+        for id in 0..5 {
+            sampler.add(puzzle(&id.to_string()));
+        }
+        let ids: Vec<String> = sampler.into_puzzles().into_iter().map(|puzzle| puzzle.puzzle_id).collect();
+        assert_eq!(ids, ["0", "1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn sampler_is_repeatable_with_a_seed() {
+        let sample = |seed| {
+            let mut sampler = PuzzleSampler::new(10, Some(seed));
+            for id in 0..1000 {
+                sampler.add(puzzle(&id.to_string()));
+            }
+            sampler.into_puzzles().into_iter().map(|puzzle| puzzle.puzzle_id.parse::<usize>().unwrap()).collect::<Vec<usize>>()
+        };
+        let first = sample(42);
+        assert_eq!(first.len(), 10);
+        // Given back in file order
+        assert!(first.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(first, sample(42));
+        assert_ne!(first, sample(43));
+    }
+}
@@ -1,9 +1,22 @@
-use iced::widget::{Button, Container, Checkbox, column, Column, Text, TextInput, row, PickList, Scrollable};
-use iced::{alignment, Command, Alignment, Element, Length};
+use iced::widget::{Button, Container, Checkbox, column, Column, Text, TextInput, Radio, row, Row, PickList, Scrollable};
+use iced::{alignment, Command, Alignment, Element, Length, Subscription, subscription};
+use iced::futures::SinkExt;
+use tokio::sync::mpsc;
 
 use iced_aw::TabLabel;
 
-use crate::{Message, Tab, config, styles, lang, lang::PickListWrapper};
+use crate::{Message, Tab, config, styles, lang, db, lang::PickListWrapper};
+
+// Where the ids of the puzzles lichess removed are listed after a merge
+const REMOVED_IDS_FILE: &str = "removed_puzzles.txt";
+
+// An import or merge of a puzzles file, kept in the settings tab
+// while the subscription that runs it is active.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PuzzleDbJob {
+    Import(String),
+    Merge(String),
+}
 
 #[derive(Debug, Clone)]
 pub enum SettingsMessage {
@@ -11,6 +24,8 @@ pub enum SettingsMessage {
     CheckAutoLoad(bool),
     CheckFlipBoard(bool),
     CheckShowCoords(bool),
+    SelectBoardDisplay(config::BoardDisplay),
+    ChangeFadeSeconds(String),
     SelectPieceTheme(styles::PieceTheme),
     SelectBoardTheme(styles::Theme),
     SelectLanguage(PickListWrapper<lang::Language>),
@@ -18,7 +33,18 @@ pub enum SettingsMessage {
     ChangePuzzleDbLocation(String),
     ChangeSearchResultLimit(String),
     ChangeEnginePath(String),
-    ChangePressed
+    ChangePressed,
+    ImportPuzzleDb,
+    PuzzleDbProgressed(u64),
+    PuzzleDbImported(Option<usize>),
+    ChangeNewPuzzleFile(String),
+    MergePuzzleDb,
+    PuzzleDbMerged(Result<db::MergeReport, db::MergeError>),
+    RemoveMissingPuzzles,
+    MissingPuzzlesRemoved(Option<usize>),
+    SelectProfile(String),
+    ChangeNewProfileName(String),
+    CreateProfile,
 }
 
 pub struct SettingsTab {
@@ -34,12 +60,27 @@ pub struct SettingsTab {
     auto_load_next: bool,
     pub flip_board: bool,
     pub show_coordinates: bool,
+    pub board_display: config::BoardDisplay,
+    pub fade_seconds: String,
 
     puzzle_db_location_value: String,
     search_results_limit_value: String,
 
     settings_status: String,
+    import_status: String,
+    pub puzzle_db_job: Option<PuzzleDbJob>,
+    // The csv the puzzles table was filled from, the searches use it instead of the setting
+    puzzle_source: Option<(String, i64)>,
+    new_puzzle_file_value: String,
+    // The puzzles the last merge didn't find in the newer file, removed if the user confirms it
+    missing_puzzles: Vec<String>,
     pub saved_configs: config::OfflinePuzzlesConfig,
+
+    // (id, name) of every profile
+    pub profiles: Vec<(i32, String)>,
+    pub active_profile: i32,
+    new_profile_value: String,
+    profile_status: String,
 }
 
 impl SettingsTab {
@@ -57,10 +98,21 @@ impl SettingsTab {
             auto_load_next: config::SETTINGS.auto_load_next,
             flip_board: config::SETTINGS.flip_board,
             show_coordinates: config::SETTINGS.show_coordinates,
+            board_display: config::SETTINGS.board_display,
+            fade_seconds: config::SETTINGS.fade_seconds.to_string(),
             puzzle_db_location_value: String::from(&config::SETTINGS.puzzle_db_location),
             search_results_limit_value: config::SETTINGS.search_results_limit.to_string(),
             settings_status: String::new(),
+            import_status: String::new(),
+            puzzle_db_job: None,
+            puzzle_source: db::puzzle_source(),
+            new_puzzle_file_value: String::new(),
+            missing_puzzles: Vec::new(),
             saved_configs: config::load_config(),
+            profiles: db::profiles(),
+            active_profile: db::active_profile(),
+            new_profile_value: String::new(),
+            profile_status: String::new(),
         }
     }
 
@@ -112,6 +164,18 @@ impl SettingsTab {
                 self.show_coordinates = value;
                 Command::none()
             }
+            SettingsMessage::SelectBoardDisplay(value) => {
+                self.board_display = value;
+                Command::none()
+            }
+            SettingsMessage::ChangeFadeSeconds(value) => {
+                if value.parse::<u64>().is_ok() {
+                    self.fade_seconds = value;
+                } else if value.is_empty() {
+                    self.fade_seconds = String::from("0");
+                }
+                Command::none()
+            }
             SettingsMessage::ChangePDFExportPgs(value) => {
                 if let Ok(_) = value.parse::<i32>() {
                     self.export_pgs = value;
@@ -142,10 +206,23 @@ impl SettingsTab {
                     export_pgs: self.export_pgs.parse().unwrap(),
                     last_min_rating: self.saved_configs.last_min_rating,
                     last_max_rating: self.saved_configs.last_max_rating,
+                    last_min_moves: self.saved_configs.last_min_moves,
+                    last_max_moves: self.saved_configs.last_max_moves,
                     last_theme: self.saved_configs.last_theme,
-                    last_opening: self.saved_configs.last_opening,
+                    last_theme_filter: self.saved_configs.last_theme_filter.clone(),
+                    last_stats_filter: self.saved_configs.last_stats_filter,
+                    last_opening: self.saved_configs.last_opening.clone(),
                     last_variation: self.saved_configs.last_variation.clone(),
                     last_opening_side: self.saved_configs.last_opening_side,
+                    last_position_pattern: self.saved_configs.last_position_pattern.clone(),
+                    last_opening_line: self.saved_configs.last_opening_line.clone(),
+                    last_order: self.saved_configs.last_order,
+                    last_level_window: self.saved_configs.last_level_window,
+                    // The presets may have changed in the search tab since we loaded the config
+                    saved_searches: config::load_config().saved_searches,
+                    active_profile: self.active_profile,
+                    board_display: self.board_display,
+                    fade_seconds: self.fade_seconds.parse().unwrap(),
                 };
                 let file = std::fs::File::create("settings.json");
                 match file {
@@ -158,10 +235,169 @@ impl SettingsTab {
                     } Err(_) => self.settings_status = lang::tr(&self.lang.lang, "error_reading_config")
                 }
                 Command::none()
+            } SettingsMessage::ImportPuzzleDb => {
+                if self.puzzle_db_job.is_none() {
+                    self.import_status = lang::tr(&self.lang.lang, "importing_puzzles");
+                    self.puzzle_db_job = Some(PuzzleDbJob::Import(self.puzzle_db_location_value.clone()));
+                }
+                Command::none()
+            } SettingsMessage::PuzzleDbProgressed(percent) => {
+                self.import_status = lang::tr(&self.lang.lang, "importing_puzzles") + " " + &percent.to_string() + "%";
+                Command::none()
+            } SettingsMessage::PuzzleDbImported(result) => {
+                self.puzzle_db_job = None;
+                self.import_status = match result {
+                    Some(count) => count.to_string() + &lang::tr(&self.lang.lang, "puzzles_imported"),
+                    None => lang::tr(&self.lang.lang, "error_importing"),
+                };
+                self.puzzle_source = db::puzzle_source();
+                Command::none()
+            } SettingsMessage::ChangeNewPuzzleFile(value) => {
+                self.new_puzzle_file_value = value;
+                Command::none()
+            } SettingsMessage::MergePuzzleDb => {
+                if self.puzzle_db_job.is_none() {
+                    self.import_status = lang::tr(&self.lang.lang, "importing_puzzles");
+                    self.puzzle_db_job = Some(PuzzleDbJob::Merge(self.new_puzzle_file_value.clone()));
+                }
+                Command::none()
+            } SettingsMessage::PuzzleDbMerged(result) => {
+                self.puzzle_db_job = None;
+                self.import_status = match result {
+                    Ok(report) => {
+                        let mut status = lang::tr(&self.lang.lang, "puzzles_added") + &report.added.to_string() + ", " +
+                            &lang::tr(&self.lang.lang, "puzzles_updated") + &report.updated.to_string() + ", " +
+                            &lang::tr(&self.lang.lang, "puzzles_missing") + &report.removed.len().to_string() + ", " +
+                            &lang::tr(&self.lang.lang, "favs_refreshed") + &report.favs_refreshed.to_string();
+                        if !report.removed.is_empty() {
+                            status = status + "\n" + &lang::tr(&self.lang.lang, "removed_ids_saved") + REMOVED_IDS_FILE;
+                        }
+                        self.missing_puzzles = report.removed;
+                        self.puzzle_source = db::puzzle_source();
+                        status
+                    }
+                    Err(db::MergeError::Parse(error)) => lang::tr(&self.lang.lang, "merge_parse_error") + &error,
+                    Err(db::MergeError::TooFewPuzzles { found, existing }) =>
+                        lang::tr(&self.lang.lang, "merge_too_few") + &format!("{} / {}", found, existing),
+                    Err(db::MergeError::Open) | Err(db::MergeError::Database(_)) => lang::tr(&self.lang.lang, "error_importing"),
+                };
+                Command::none()
+            } SettingsMessage::RemoveMissingPuzzles => {
+                let ids = std::mem::take(&mut self.missing_puzzles);
+                Command::perform(
+                    SettingsTab::remove_puzzles(ids),
+                    |result| Message::Settings(SettingsMessage::MissingPuzzlesRemoved(result)))
+            } SettingsMessage::MissingPuzzlesRemoved(result) => {
+                self.import_status = match result {
+                    Some(count) => lang::tr(&self.lang.lang, "puzzles_removed") + &count.to_string(),
+                    None => lang::tr(&self.lang.lang, "error_importing"),
+                };
+                Command::none()
+            } SettingsMessage::SelectProfile(name) => {
+                match self.profiles.iter().find(|(_, profile)| *profile == name) {
+                    Some(&(id, _)) if id != self.active_profile => {
+                        Command::perform(SettingsTab::send_profile(id), Message::ProfileSelected)
+                    }
+                    _ => Command::none()
+                }
+            } SettingsMessage::ChangeNewProfileName(value) => {
+                self.new_profile_value = value;
+                Command::none()
+            } SettingsMessage::CreateProfile => {
+                let name = self.new_profile_value.trim().to_string();
+                if name.is_empty() {
+                    return Command::none();
+                }
+                match db::create_profile(&name) {
+                    Some(id) => {
+                        self.profiles = db::profiles();
+                        self.new_profile_value.clear();
+                        self.profile_status = lang::tr(&self.lang.lang, "profile_created");
+                        Command::perform(SettingsTab::send_profile(id), Message::ProfileSelected)
+                    } None => {
+                        self.profile_status = lang::tr(&self.lang.lang, "profile_name_taken");
+                        Command::none()
+                    }
+                }
             }
         }
     }
 
+    pub async fn send_profile(profile_id: i32) -> i32 {
+        profile_id
+    }
+
+    pub fn save_active_profile(profile_id: i32) {
+        let mut config = config::load_config();
+        config.active_profile = profile_id;
+        let file = std::fs::File::create("settings.json");
+        match file {
+            Ok(file) => {
+                if serde_json::to_writer_pretty(file, &config).is_err() {
+                    println!("Error saving config file.");
+                }
+            } Err(_) => println!("Error opening settings file")
+        }
+    }
+
+    // The csv in the settings was replaced or isn't the one the database was filled from
+    fn puzzle_source_changed(&self) -> bool {
+        match &self.puzzle_source {
+            Some((csv_path, modified_at)) => *csv_path != self.puzzle_db_location_value ||
+                *modified_at != db::file_modified_at(&self.puzzle_db_location_value),
+            None => false,
+        }
+    }
+
+    // Imports or merges the puzzles file in its own thread like the search,
+    // sending how much of the file was read to the settings tab.
+    pub fn run_puzzle_db_job(job: PuzzleDbJob) -> Subscription<Message> {
+        subscription::channel(
+            (std::any::TypeId::of::<PuzzleDbJob>(), job.clone()),
+            100,
+            move |mut output| {
+                let job = job.clone();
+
+                async move {
+                    let (sender, mut receiver) = mpsc::channel(100);
+                    std::thread::spawn(move || {
+                        let progress_sender = sender.clone();
+                        let mut report = |percent| {
+                            let _ = progress_sender.blocking_send(Message::Settings(SettingsMessage::PuzzleDbProgressed(percent)));
+                        };
+                        let msg = match job {
+                            PuzzleDbJob::Import(csv_path) => {
+                                SettingsMessage::PuzzleDbImported(db::import_puzzles(&csv_path, &mut report))
+                            } PuzzleDbJob::Merge(csv_path) => {
+                                SettingsMessage::PuzzleDbMerged(SettingsTab::merge_puzzles(&csv_path, &mut report))
+                            }
+                        };
+                        let _ = sender.blocking_send(Message::Settings(msg));
+                    });
+                    loop {
+                        match receiver.recv().await {
+                            Some(msg) => output.send(msg).await.expect("Error on the mpsc channel in the puzzles import subscription"),
+                            // Import done, we just wait for the subscription to be dropped
+                            None => tokio::time::sleep(std::time::Duration::from_millis(100)).await,
+                        }
+                    }
+                }
+            }
+        )
+    }
+
+    fn merge_puzzles(csv_path: &str, progress: &mut dyn FnMut(u64)) -> Result<db::MergeReport, db::MergeError> {
+        let report = db::merge_puzzles(csv_path, progress)?;
+        if !report.removed.is_empty() && std::fs::write(REMOVED_IDS_FILE, report.removed.join("\n")).is_err() {
+            println!("Error saving the removed puzzle ids.");
+        }
+        Ok(report)
+    }
+
+    pub async fn remove_puzzles(ids: Vec<String>) -> Option<usize> {
+        db::remove_puzzles(&ids)
+    }
+
     pub fn save_window_size(width: u32, height: u32) {
         let mut config = config::load_config();
         config.window_width = width;
@@ -207,7 +443,33 @@ impl Tab for SettingsTab {
     }
 
     fn content(&self) -> Element<Message, iced::Renderer<styles::Theme>> {
+        let active_profile = self.profiles.iter()
+            .find(|(id, _)| *id == self.active_profile)
+            .map(|(_, name)| name.clone());
+        let mut display_row = Row::new().spacing(10).align_items(Alignment::Center)
+            .push(Text::new(lang::tr(&self.lang.lang, "board_display")));
+        for display in config::BoardDisplay::ALL {
+            display_row = display_row.push(
+                Radio::new(lang::tr(&self.lang.lang, display.get_tr_key()), display, Some(self.board_display), SettingsMessage::SelectBoardDisplay)
+            );
+        }
         let col_settings = column![
+            row![
+                Text::new(lang::tr(&self.lang.lang, "profile")),
+                PickList::new(
+                    self.profiles.iter().map(|(_, name)| name.clone()).collect::<Vec<String>>(),
+                    active_profile,
+                    SettingsMessage::SelectProfile
+                )
+            ].spacing(5).align_items(Alignment::Center),
+            row![
+                TextInput::new(
+                    &lang::tr(&self.lang.lang, "new_profile"),
+                    &self.new_profile_value,
+                ).on_input(SettingsMessage::ChangeNewProfileName).on_submit(SettingsMessage::CreateProfile).width(200).padding(10).size(20),
+                Button::new(Text::new(lang::tr(&self.lang.lang, "create_profile"))).padding(5).on_press(SettingsMessage::CreateProfile),
+            ].spacing(5).align_items(Alignment::Center),
+            Text::new(&self.profile_status),
             row![
                 Text::new(lang::tr(&self.lang.lang, "piece_theme")),
                 PickList::new(
@@ -264,6 +526,14 @@ impl Tab for SettingsTab {
                     SettingsMessage::CheckShowCoords,
                 ).size(20),
             ].spacing(5).align_items(Alignment::Center),
+            display_row,
+            row![
+                Text::new(lang::tr(&self.lang.lang, "fade_seconds")),
+                TextInput::new(
+                    &self.fade_seconds,
+                    &self.fade_seconds,
+                ).on_input(SettingsMessage::ChangeFadeSeconds).width(60).padding(10).size(20),
+            ].spacing(5).align_items(Alignment::Center),
             row![
                 Text::new(lang::tr(&self.lang.lang, "pdf_number_of_pages")),
                 TextInput::new(
@@ -286,8 +556,35 @@ impl Tab for SettingsTab {
             ).on_input(SettingsMessage::ChangeEnginePath).width(200).padding(10).size(20),
             Button::new(Text::new(lang::tr(&self.lang.lang, "save"))).padding(5).on_press(SettingsMessage::ChangePressed),
             Text::new(&self.settings_status).vertical_alignment(alignment::Vertical::Bottom),
+            Text::new(match &self.puzzle_source {
+                Some((csv_path, _)) => lang::tr(&self.lang.lang, "puzzle_source") + csv_path,
+                None => lang::tr(&self.lang.lang, "puzzle_source_csv") + &self.puzzle_db_location_value,
+            }),
+            Button::new(Text::new(lang::tr(&self.lang.lang, "import_puzzle_db"))).padding(5).on_press(SettingsMessage::ImportPuzzleDb),
+            Text::new(lang::tr(&self.lang.lang, "new_puzzle_file")),
+            row![
+                TextInput::new(
+                    "puzzles/lichess_db_puzzle.csv.zst",
+                    &self.new_puzzle_file_value,
+                ).on_input(SettingsMessage::ChangeNewPuzzleFile).width(300).padding(10).size(20),
+                Button::new(Text::new(lang::tr(&self.lang.lang, "merge_puzzle_db"))).padding(5).on_press(SettingsMessage::MergePuzzleDb),
+            ].spacing(5).align_items(Alignment::Center),
+            Text::new(&self.import_status),
 
         ].spacing(10).align_items(Alignment::Center);
+        let col_settings = if self.puzzle_source_changed() {
+            col_settings.push(Text::new(lang::tr(&self.lang.lang, "puzzle_source_changed")))
+        } else {
+            col_settings
+        };
+        let col_settings = if self.missing_puzzles.is_empty() {
+            col_settings
+        } else {
+            col_settings.push(
+                Button::new(Text::new(lang::tr(&self.lang.lang, "remove_missing_puzzles") + &self.missing_puzzles.len().to_string()))
+                    .padding(5).on_press(SettingsMessage::RemoveMissingPuzzles)
+            )
+        };
         let content: Element<SettingsMessage, iced::Renderer<styles::Theme>> = Container::new(
             Scrollable::new(
                 Column::new().spacing(10).push(col_settings)
@@ -0,0 +1,241 @@
+use iced::widget::{Button, Container, column as col, Column, Radio, row, Row, Scrollable, Text};
+use iced::{alignment, Command, Alignment, Element, Length};
+use std::collections::BTreeMap;
+
+use iced_aw::TabLabel;
+
+use crate::{Message, Tab, config, styles, lang, db};
+use crate::lang::PickListWrapper;
+use crate::models::AttemptRecord;
+use crate::openings::Openings;
+use crate::search_tab::TaticsThemes;
+
+// The rating buckets are this many points wide
+const RATING_BUCKET: i32 = 200;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone)]
+pub enum StatsMessage {
+    Refresh,
+    StatsLoaded(PlayerStats),
+    SelectBreakdown(Breakdown),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakdown {
+    Theme, Opening, Rating,
+}
+
+// The results of the attempts at a group of puzzles
+#[derive(Debug, Clone, Default)]
+pub struct GroupStats {
+    pub attempts: usize,
+    pub solved: usize,
+    pub total_time_ms: i64,
+    // Only the attempts whose puzzle rating we know count for the performance
+    pub rated_attempts: usize,
+    pub rated_solved: usize,
+    pub total_rating: i64,
+}
+
+impl GroupStats {
+    fn add(&mut self, record: &AttemptRecord) {
+        self.attempts += 1;
+        if record.solved {
+            self.solved += 1;
+        }
+        self.total_time_ms += record.time_taken_ms;
+        if record.puzzle_rating > 0 {
+            self.rated_attempts += 1;
+            if record.solved {
+                self.rated_solved += 1;
+            }
+            self.total_rating += record.puzzle_rating as i64;
+        }
+    }
+
+    pub fn solve_rate(&self) -> f64 {
+        self.solved as f64 * 100. / self.attempts.max(1) as f64
+    }
+
+    pub fn average_secs(&self) -> f64 {
+        self.total_time_ms as f64 / 1000. / self.attempts.max(1) as f64
+    }
+
+    // The usual estimate of a performance rating: the average rating of the
+    // puzzles, plus 400 points for each solve above half of the attempts.
+    pub fn performance(&self) -> Option<f64> {
+        if self.rated_attempts == 0 {
+            return None;
+        }
+        let attempts = self.rated_attempts as f64;
+        Some(self.total_rating as f64 / attempts + 400. * (2. * self.rated_solved as f64 - attempts) / attempts)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PlayerStats {
+    pub overall: GroupStats,
+    // Keyed by the tag of the theme / opening family and by the start of the rating bucket
+    pub by_theme: BTreeMap<String, GroupStats>,
+    pub by_opening: BTreeMap<String, GroupStats>,
+    pub by_rating: BTreeMap<i32, GroupStats>,
+    pub rating: Option<f64>,
+    pub rating_change_week: Option<f64>,
+    pub rating_change_month: Option<f64>,
+}
+
+impl PlayerStats {
+    fn load() -> Self {
+        let mut stats = PlayerStats::default();
+        for record in db::attempt_records() {
+            stats.overall.add(&record);
+            for tag in record.themes.split_whitespace() {
+                if TaticsThemes::from_tag(tag).is_some() {
+                    stats.by_theme.entry(tag.to_string()).or_default().add(&record);
+                }
+            }
+            // The first tag is the family, the others its variations
+            if let Some(family) = record.opening_tags.split_whitespace().next() {
+                stats.by_opening.entry(family.to_string()).or_default().add(&record);
+            }
+            // Attempts saved before the puzzle rating was recorded, and that
+            // the migration couldn't fill in, don't go in any rating bucket.
+            if record.puzzle_rating > 0 {
+                let bucket = record.puzzle_rating.div_euclid(RATING_BUCKET) * RATING_BUCKET;
+                stats.by_rating.entry(bucket).or_default().add(&record);
+            }
+        }
+
+        let history = db::rating_history();
+        if let Some(&(now, rating)) = history.last() {
+            // The change since the last rating from before the period
+            let change_since = |days: i64| {
+                history.iter().rev()
+                    .find(|(rated_at, _)| *rated_at <= now - days * SECONDS_PER_DAY)
+                    .map(|(_, old_rating)| rating - old_rating)
+            };
+            stats.rating = Some(rating);
+            stats.rating_change_week = change_since(7);
+            stats.rating_change_month = change_since(30);
+        }
+        stats
+    }
+}
+
+pub struct StatsTab {
+    stats: PlayerStats,
+    breakdown: Breakdown,
+    pub lang: lang::Language,
+}
+
+impl StatsTab {
+    pub fn new() -> Self {
+        StatsTab {
+            stats: PlayerStats::default(),
+            breakdown: Breakdown::Theme,
+            lang: config::SETTINGS.lang,
+        }
+    }
+
+    pub fn update(&mut self, message: StatsMessage) -> Command<Message> {
+        match message {
+            StatsMessage::Refresh => {
+                Command::perform(StatsTab::load_stats(), |stats| Message::Stats(StatsMessage::StatsLoaded(stats)))
+            } StatsMessage::StatsLoaded(stats) => {
+                self.stats = stats;
+                Command::none()
+            } StatsMessage::SelectBreakdown(breakdown) => {
+                self.breakdown = breakdown;
+                Command::none()
+            }
+        }
+    }
+
+    pub async fn load_stats() -> PlayerStats {
+        PlayerStats::load()
+    }
+
+    fn stats_row(&self, name: String, group: &GroupStats) -> Row<'_, StatsMessage, iced::Renderer<styles::Theme>> {
+        row![
+            Text::new(name).width(Length::FillPortion(3)),
+            Text::new(group.attempts.to_string()).width(Length::FillPortion(1)),
+            Text::new(format!("{:.0}%", group.solve_rate())).width(Length::FillPortion(1)),
+            Text::new(format!("{:.1}s", group.average_secs())).width(Length::FillPortion(1)),
+            Text::new(group.performance().map_or(String::from("-"), |performance| format!("{:.0}", performance))).width(Length::FillPortion(1)),
+        ].spacing(5)
+    }
+
+    fn rating_trend(&self) -> String {
+        let change = |change: Option<f64>| change.map_or(String::from("-"), |change| format!("{:+.0}", change));
+        match self.stats.rating {
+            Some(rating) => format!("{}{:.0}   {}{}   {}{}",
+                lang::tr(&self.lang, "your_rating"), rating,
+                lang::tr(&self.lang, "stats_last_week"), change(self.stats.rating_change_week),
+                lang::tr(&self.lang, "stats_last_month"), change(self.stats.rating_change_month)),
+            None => lang::tr(&self.lang, "your_rating") + "-",
+        }
+    }
+}
+
+impl Tab for StatsTab {
+    type Message = Message;
+
+    fn title(&self) -> String {
+        lang::tr(&self.lang, "stats")
+    }
+
+    fn tab_label(&self) -> TabLabel {
+        TabLabel::Text(self.title())
+    }
+
+    fn content(&self) -> Element<'_, Message, iced::Renderer<styles::Theme>> {
+        let header = row![
+            Text::new("").width(Length::FillPortion(3)),
+            Text::new(lang::tr(&self.lang, "stats_attempts")).width(Length::FillPortion(1)),
+            Text::new(lang::tr(&self.lang, "stats_solved")).width(Length::FillPortion(1)),
+            Text::new(lang::tr(&self.lang, "stats_avg_time")).width(Length::FillPortion(1)),
+            Text::new(lang::tr(&self.lang, "stats_performance")).width(Length::FillPortion(1)),
+        ].spacing(5);
+
+        let mut groups = Column::new().spacing(5).push(header)
+            .push(self.stats_row(lang::tr(&self.lang, "stats_overall"), &self.stats.overall));
+        match self.breakdown {
+            Breakdown::Theme => {
+                for (tag, group) in &self.stats.by_theme {
+                    if let Some(theme) = TaticsThemes::from_tag(tag) {
+                        let name = PickListWrapper { lang: self.lang, item: theme }.to_string();
+                        groups = groups.push(self.stats_row(name, group));
+                    }
+                }
+            } Breakdown::Opening => {
+                for (family, group) in &self.stats.by_opening {
+                    let name = PickListWrapper { lang: self.lang, item: Openings::new(family) }.to_string();
+                    groups = groups.push(self.stats_row(name, group));
+                }
+            } Breakdown::Rating => {
+                for (bucket, group) in &self.stats.by_rating {
+                    let name = format!("{} - {}", bucket, bucket + RATING_BUCKET - 1);
+                    groups = groups.push(self.stats_row(name, group));
+                }
+            }
+        }
+
+        let col_stats = col![
+            Text::new(self.rating_trend()),
+            row![
+                Text::new(lang::tr(&self.lang, "stats_by")),
+                Radio::new(lang::tr(&self.lang, "stats_by_theme"), Breakdown::Theme, Some(self.breakdown), StatsMessage::SelectBreakdown),
+                Radio::new(lang::tr(&self.lang, "stats_by_opening"), Breakdown::Opening, Some(self.breakdown), StatsMessage::SelectBreakdown),
+                Radio::new(lang::tr(&self.lang, "stats_by_rating"), Breakdown::Rating, Some(self.breakdown), StatsMessage::SelectBreakdown),
+            ].spacing(10).align_items(Alignment::Center),
+            Scrollable::new(groups).height(Length::Fill),
+            Button::new(Text::new(lang::tr(&self.lang, "stats_refresh"))).padding(5).on_press(StatsMessage::Refresh),
+        ].spacing(10).align_items(Alignment::Center);
+
+        let content: Element<StatsMessage, iced::Renderer<styles::Theme>> = Container::new(col_stats)
+            .align_x(alignment::Horizontal::Center).height(Length::Fill).into();
+
+        content.map(Message::Stats)
+    }
+}
@@ -26,6 +26,7 @@ correct_puzzle = Well done!
 wrong_move_white_play = Oops! Wrong move... White to play.
 wrong_move_black_play = Oops! Wrong move... Black to play.
 all_puzzles_done = All puzzles done for this search!
+your_rating = Your rating:{" "}
 no_puzzle_found = Sorry, no puzzle found.
 
 #Notation
@@ -38,16 +39,106 @@ king = K
 #Search Tab
 lichess_db = Lichess DB
 my_favories = My Favorites
+at_my_level = At my level
+level_window = Distance from my rating:{" "}
 min_rating = Min. Rating:{" "}
 max_rating = Max. Rating:{" "}
+min_moves = Min. moves to play:{" "}
+max_moves = Max. moves to play:{" "}
+min_popularity = Min. Popularity:{" "}
+max_popularity = Max. Popularity:{" "}
+min_rd = Min. Rating Deviation:{" "}
+max_rd = Max. Rating Deviation:{" "}
+times_played_range = Times played:
+no_limit = No limit
 theme_label = Tactics theme:
+more_themes = Combine with more themes:
+theme_all_of = Must have
+theme_any_of = Any of
+theme_none_of = Must not have
 in_opening = In the opening:
 in_the_variation = Variation:
+loading_openings = Reading the openings from the puzzle file:{" "}
+opening_line = Or the opening moves or ECO codes:
+unknown_opening_line = No known opening matches these moves or ECO codes.
 side = Side:
 any = Any
 white = White
 black = Black
+position_pattern = Position pattern (optional):
+position_pattern_help = e.g. "RP vs R" (white vs black material), "kg8 pf7" (black king on g8, pawn on f7), "-Qq" (no queens) or a FEN fragment like "5ppp/"
+invalid_pattern = Invalid position pattern.
 searching = Searching, please wait...
+puzzles_read = Puzzles read:{" "}
+puzzles_found = found:{" "}
+cancel_search = Cancel
+search_cancelled = Search cancelled, puzzles found:{" "}
+load_partial_results = Load them
+puzzle_id_list = Or load a list of puzzle ids (or the path of a file with them):
+load_id_list = Load
+review_due = Review the failed puzzles that are due
+puzzle_rush = Puzzle rush:{" "}
+rush_time = Time:{" "}
+rush_score = Score:{" "}
+rush_strikes = Strikes:{" "}
+rush_best = Best:{" "}
+rush_new_best = New personal best!
+rush_over = The run is over!
+stop_rush = Stop the run
+stats = Statistics
+stats_attempts = Attempts
+stats_solved = Solved
+stats_avg_time = Avg. time
+stats_performance = Performance
+stats_overall = All puzzles
+stats_by = By:{" "}
+stats_by_theme = Theme
+stats_by_opening = Opening
+stats_by_rating = Rating
+stats_refresh = Refresh
+stats_last_week = Last 7 days:{" "}
+stats_last_month = Last 30 days:{" "}
+profile = Profile:
+new_profile = New profile name
+create_profile = Create profile
+profile_created = Profile created.
+profile_name_taken = There's already a profile with this name.
+board_display = Pieces:
+display_normal = Normal
+display_blindfold = Blindfold
+display_fading = Fading
+display_monochrome = One colour
+fade_seconds = Hide the pieces after (seconds):
+position_fen = FEN:{" "}
+moves_played = Moves:{" "}
+type_move = Type a move
+play_move = Play
+invalid_typed_move = That move isn't legal here.
+session_review = Session review
+puzzle_info = Puzzle info
+session_empty = No puzzle attempted in this set yet.
+outcome_solved = Solved
+outcome_hints = Solved with hints
+outcome_failed = Failed
+replay = Replay
+analyze = Analyze
+start_streak = Streak
+streak_length = Streak:{" "}
+no_ids = No puzzle ids to load.
+unresolved_ids = Puzzles not found:{" "}
+seed = Random seed (optional):
+last_seed = Seed of the last search:{" "}
+puzzle_order = Order:{" "}
+order_random = Random
+order_rating_asc = Rating, ascending
+order_rating_desc = Rating, descending
+order_popularity = Most popular first
+order_file = Order in the file
+saved_searches = Saved searches:{" "}
+preset_name = Search name
+save_preset = Save
+rename_preset = Rename
+delete_preset = Delete
 btn_search = Search
 promotion_piece = Promotion piece:
 
@@ -65,13 +156,31 @@ auto_load = Auto load next puzzle:
 flip_board = Flip board:
 show_coords = Show coordinates:
 pdf_number_of_pages = PDF Export no. of pages:
-get_first_puzzles1 = Get the first
+get_first_puzzles1 = Get up to
 get_first_puzzles2 =  {" "}puzzles
 engine_path = Engine path (with .exe name):
 save = Save Changes
 settings_saved = Settings saved!
 error_saving = Error saving config file.
 error_reading_config = Error reading config file.
+import_puzzle_db = Import the puzzle file into the local database
+importing_puzzles = Importing, this may take a few minutes...
+puzzles_imported = {" "}puzzles imported, searches will use the local database.
+error_importing = Error importing the puzzle file.
+puzzle_source = Searches use the puzzles imported from:{" "}
+puzzle_source_csv = Searches read the puzzle file:{" "}
+puzzle_source_changed = The puzzle file in the settings changed since it was imported, import it again to search it.
+new_puzzle_file = Newer lichess puzzle file:
+merge_puzzle_db = Update the local database
+puzzles_added = Added:{" "}
+puzzles_updated = updated:{" "}
+puzzles_removed = Puzzles removed:{" "}
+puzzles_missing = no longer in the file:{" "}
+remove_missing_puzzles = Remove the puzzles that are no longer in the file:{" "}
+merge_parse_error = The file couldn't be read to the end, nothing was changed:{" "}
+merge_too_few = The file has far fewer puzzles than the database, nothing was changed. Puzzles in the file / in the database:{" "}
+favs_refreshed = favorites refreshed:{" "}
+removed_ids_saved = The ids of the puzzles no longer in the file were saved to{" "}
 
 #Puzzles
 #============================================================
@@ -26,6 +26,7 @@ correct_puzzle = Muy bien!
 wrong_move_white_play = No! Ese no es el movimiento... Blancas juegan.
 wrong_move_black_play = No! Ese no es el movimiento... Negras juegan.
 all_puzzles_done = Ya ha hecho todos los ejercícios de esta búsqueda!
+your_rating = Su rating:{" "}
 no_puzzle_found = Lo siento, ningún ejercício encontrado.
 
 #Notation
@@ -38,16 +39,106 @@ king = R
 #Search Tab
 lichess_db = Base de datos de Lichess
 my_favories = Mis favoritos
+at_my_level = A mi nivel
+level_window = Distancia de mi rating:{" "}
 min_rating = Rating Min.:{" "}
 max_rating = Rating Max.:{" "}
+min_moves = Mín. de jugadas a realizar:{" "}
+max_moves = Máx. de jugadas a realizar:{" "}
+min_popularity = Popularidad Mín.:{" "}
+max_popularity = Popularidad Máx.:{" "}
+min_rd = Desviación de Rating Mín.:{" "}
+max_rd = Desviación de Rating Máx.:{" "}
+times_played_range = Veces jugado:
+no_limit = Sin límite
 theme_label = Tema táctico:
+more_themes = Combinar con más temas:
+theme_all_of = Debe tener
+theme_any_of = Alguno de
+theme_none_of = No debe tener
 in_opening = En la apertura:
 in_the_variation = Variante:
+loading_openings = Leyendo las aperturas del archivo de ejercícios:{" "}
+opening_line = O las jugadas de la apertura o códigos ECO:
+unknown_opening_line = Ninguna apertura conocida corresponde a esas jugadas o códigos ECO.
 side = Lado:
 any = Ambos
 white = Blancas
 black = Negras
+position_pattern = Patrón de posición (opcional):
+position_pattern_help = ej. "RP vs R" (material de blancas vs negras), "kg8 pf7" (rey negro en g8, peón en f7), "-Qq" (sin damas) o parte de un FEN como "5ppp/"
+invalid_pattern = Patrón de posición inválido.
 searching = Buscando, aguarde...
+puzzles_read = Ejercícios leídos:{" "}
+puzzles_found = encontrados:{" "}
+cancel_search = Cancelar
+search_cancelled = Búsqueda cancelada, ejercícios encontrados:{" "}
+load_partial_results = Cargarlos
+puzzle_id_list = O cargue una lista de ids de ejercícios (o la ruta de un archivo con ellos):
+load_id_list = Cargar
+review_due = Repasar los ejercícios fallados pendientes
+puzzle_rush = Puzzle rush:{" "}
+rush_time = Tiempo:{" "}
+rush_score = Puntos:{" "}
+rush_strikes = Errores:{" "}
+rush_best = Récord:{" "}
+rush_new_best = ¡Nuevo récord personal!
+rush_over = ¡Se acabó la partida!
+stop_rush = Parar la partida
+stats = Estadísticas
+stats_attempts = Intentos
+stats_solved = Resueltos
+stats_avg_time = Tiempo medio
+stats_performance = Rendimiento
+stats_overall = Todos los ejercícios
+stats_by = Por:{" "}
+stats_by_theme = Tema
+stats_by_opening = Apertura
+stats_by_rating = Rating
+stats_refresh = Actualizar
+stats_last_week = Últimos 7 días:{" "}
+stats_last_month = Últimos 30 días:{" "}
+profile = Perfil:
+new_profile = Nombre del nuevo perfil
+create_profile = Crear perfil
+profile_created = Perfil creado.
+profile_name_taken = Ya existe un perfil con ese nombre.
+board_display = Piezas:
+display_normal = Normal
+display_blindfold = A ciegas
+display_fading = Desvaneciendo
+display_monochrome = Un color
+fade_seconds = Ocultar las piezas después de (segundos):
+position_fen = FEN:{" "}
+moves_played = Jugadas:{" "}
+type_move = Escribe una jugada
+play_move = Jugar
+invalid_typed_move = Esa jugada no es legal aquí.
+session_review = Revisión de la sesión
+puzzle_info = Info del ejercício
+session_empty = Aún no se intentó ningún ejercício de este conjunto.
+outcome_solved = Resuelto
+outcome_hints = Resuelto con pistas
+outcome_failed = Fallado
+replay = Repetir
+analyze = Analizar
+start_streak = Racha
+streak_length = Racha:{" "}
+no_ids = No hay ids de ejercícios para cargar.
+unresolved_ids = Ejercícios no encontrados:{" "}
+seed = Semilla aleatoria (opcional):
+last_seed = Semilla de la última búsqueda:{" "}
+puzzle_order = Orden:{" "}
+order_random = Aleatorio
+order_rating_asc = Rating, ascendente
+order_rating_desc = Rating, descendente
+order_popularity = Más populares primero
+order_file = Orden del archivo
+saved_searches = Búsquedas guardadas:{" "}
+preset_name = Nombre de la búsqueda
+save_preset = Guardar
+rename_preset = Renombrar
+delete_preset = Eliminar
 btn_search = Buscar
 promotion_piece = Pieza de Promoción:
 
@@ -65,13 +156,31 @@ auto_load = Cargar automaticamente el próx. ejercício:
 flip_board = Girar el tablero:
 show_coords = Coordenadas del tablero:
 pdf_number_of_pages = N. de páginas para exportar en PDF:
-get_first_puzzles1 = Obtener los primeros
+get_first_puzzles1 = Obtener hasta
 get_first_puzzles2 =  {" "}ejercícios
 engine_path = Camino del motor de ajedrez (con el nombre del .exe):
 save = Guardar Cambios
 settings_saved = Preferencias guardadas!
 error_saving = Error al guardar el fichero con las preferencias.
 error_reading_config = Error al leer el fichero con las preferencias.
+import_puzzle_db = Importar el archivo de ejercícios a la base de datos local
+importing_puzzles = Importando, esto puede tardar unos minutos...
+puzzles_imported = {" "}ejercícios importados, las búsquedas usarán la base de datos local.
+error_importing = Error al importar el archivo de ejercícios.
+puzzle_source = Las búsquedas usan los ejercícios importados de:{" "}
+puzzle_source_csv = Las búsquedas leen el archivo de ejercícios:{" "}
+puzzle_source_changed = El archivo de ejercícios en la configuración cambió desde que se importó, impórtelo de nuevo para buscar en él.
+new_puzzle_file = Archivo de ejercícios más reciente de lichess:
+merge_puzzle_db = Actualizar la base de datos local
+puzzles_added = Añadidos:{" "}
+puzzles_updated = actualizados:{" "}
+puzzles_removed = Ejercícios eliminados:{" "}
+puzzles_missing = que ya no están en el archivo:{" "}
+remove_missing_puzzles = Eliminar los ejercícios que ya no están en el archivo:{" "}
+merge_parse_error = No se pudo leer el archivo hasta el final, no se cambió nada:{" "}
+merge_too_few = El archivo tiene muchos menos ejercícios que la base de datos, no se cambió nada. Ejercícios en el archivo / en la base de datos:{" "}
+favs_refreshed = favoritos actualizados:{" "}
+removed_ids_saved = Los ids de los ejercícios que ya no están en el archivo se guardaron en{" "}
 
 #Puzzles
 #============================================================
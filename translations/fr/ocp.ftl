@@ -28,6 +28,7 @@ correct_puzzle = Bravo !
 wrong_move_white_play = Oups ! Erreur... Trait aux Blancs.
 wrong_move_black_play = Oups ! Erreur... Trait aux Noirs.
 all_puzzles_done = Tous les puzzles ont été réalisés pour cette recherche !
+your_rating = Votre classement :{" "}
 no_puzzle_found = Désolé, aucun puzzle n'a été trouvé.
 
 #Notation
@@ -40,16 +41,106 @@ king = R
 #Search Tab
 lichess_db = BdD Lichess
 my_favories = Mes favoris
+at_my_level = À mon niveau
+level_window = Écart avec mon classement :{" "}
 min_rating = Classement minimal:{" "}
 max_rating = Classement maximal:{" "}
+min_moves = Nombre minimal de coups à jouer:{" "}
+max_moves = Nombre maximal de coups à jouer:{" "}
+min_popularity = Popularité minimale:{" "}
+max_popularity = Popularité maximale:{" "}
+min_rd = Ecart-type du classement minimal:{" "}
+max_rd = Ecart-type du classement maximal:{" "}
+times_played_range = Nombre de fois joué:
+no_limit = Sans limite
 theme_label = Thème tactique:
+more_themes = Combiner avec d'autres thèmes:
+theme_all_of = Doit avoir
+theme_any_of = Un parmi
+theme_none_of = Ne doit pas avoir
 in_opening = Issu de l'ouverture:
 in_the_variation = Avec la variante:
+loading_openings = Lecture des ouvertures du fichier de puzzles:{" "}
+opening_line = Ou les coups de l'ouverture ou codes ECO:
+unknown_opening_line = Aucune ouverture connue ne correspond à ces coups ou codes ECO.
 side = Côté:
 any = Les deux
 white = Blancs
 black = Noirs
+position_pattern = Motif de position (facultatif):
+position_pattern_help = ex. "RP vs R" (matériel des blancs vs noirs), "kg8 pf7" (roi noir en g8, pion en f7), "-Qq" (sans dames) ou un fragment de FEN comme "5ppp/"
+invalid_pattern = Motif de position invalide.
 searching = Recherche en cours, veuillez patienter...
+puzzles_read = Puzzles lus:{" "}
+puzzles_found = trouvés:{" "}
+cancel_search = Annuler
+search_cancelled = Recherche annulée, puzzles trouvés:{" "}
+load_partial_results = Les charger
+puzzle_id_list = Ou chargez une liste d'ids de puzzles (ou le chemin d'un fichier les contenant):
+load_id_list = Charger
+review_due = Réviser les puzzles ratés à revoir
+puzzle_rush = Puzzle rush :{" "}
+rush_time = Temps :{" "}
+rush_score = Score :{" "}
+rush_strikes = Erreurs :{" "}
+rush_best = Record :{" "}
+rush_new_best = Nouveau record personnel !
+rush_over = La partie est terminée !
+stop_rush = Arrêter la partie
+stats = Statistiques
+stats_attempts = Essais
+stats_solved = Réussis
+stats_avg_time = Temps moyen
+stats_performance = Performance
+stats_overall = Tous les puzzles
+stats_by = Par :{" "}
+stats_by_theme = Thème
+stats_by_opening = Ouverture
+stats_by_rating = Classement
+stats_refresh = Actualiser
+stats_last_week = 7 derniers jours :{" "}
+stats_last_month = 30 derniers jours :{" "}
+profile = Profil :
+new_profile = Nom du nouveau profil
+create_profile = Créer un profil
+profile_created = Profil créé.
+profile_name_taken = Un profil avec ce nom existe déjà.
+board_display = Pièces :
+display_normal = Normal
+display_blindfold = À l'aveugle
+display_fading = Qui s'effacent
+display_monochrome = Une seule couleur
+fade_seconds = Cacher les pièces après (secondes) :
+position_fen = FEN :{" "}
+moves_played = Coups :{" "}
+type_move = Tapez un coup
+play_move = Jouer
+invalid_typed_move = Ce coup n'est pas légal ici.
+session_review = Bilan de la session
+puzzle_info = Infos du puzzle
+session_empty = Aucun puzzle tenté dans cette série pour l'instant.
+outcome_solved = Résolu
+outcome_hints = Résolu avec indices
+outcome_failed = Raté
+replay = Rejouer
+analyze = Analyser
+start_streak = Série
+streak_length = Série :{" "}
+no_ids = Aucun id de puzzle à charger.
+unresolved_ids = Puzzles introuvables:{" "}
+seed = Graine aléatoire (facultatif):
+last_seed = Graine de la dernière recherche:{" "}
+puzzle_order = Ordre:{" "}
+order_random = Aléatoire
+order_rating_asc = Classement, croissant
+order_rating_desc = Classement, décroissant
+order_popularity = Les plus populaires d'abord
+order_file = Ordre du fichier
+saved_searches = Recherches enregistrées:{" "}
+preset_name = Nom de la recherche
+save_preset = Enregistrer
+rename_preset = Renommer
+delete_preset = Supprimer
 btn_search = Rechercher
 promotion_piece = Choix de la promotion:
 #Settings
@@ -66,13 +157,31 @@ auto_load = Passer immédiatement au puzzle suivant:
 flip_board = Tourner l'échiquier:
 show_coords = Montrer les coordonnées:
 pdf_number_of_pages = Limite de pages pour le PDF:
-get_first_puzzles1 = Accéder aux
-get_first_puzzles2 = {" "}premiers puzzles
+get_first_puzzles1 = Accéder à
+get_first_puzzles2 = {" "}puzzles maximum
 engine_path = Chemin d'accès du moteur (avec le nom du fichier .exe):
 save = Enregistrer les modifications
 settings_saved = Paramètres enregistrés !
 error_saving = Erreur lors de l'enregistrement du fichier de configuration.
 error_reading_config = Erreur de lecture du fichier de configuration.
+import_puzzle_db = Importer le fichier de puzzles dans la base de données locale
+importing_puzzles = Importation, cela peut prendre quelques minutes...
+puzzles_imported = {" "}puzzles importés, les recherches utiliseront la base de données locale.
+error_importing = Erreur lors de l'importation du fichier de puzzles.
+puzzle_source = Les recherches utilisent les puzzles importés de :{" "}
+puzzle_source_csv = Les recherches lisent le fichier de puzzles :{" "}
+puzzle_source_changed = Le fichier de puzzles des paramètres a changé depuis son import, importez-le à nouveau pour y chercher.
+new_puzzle_file = Fichier de puzzles lichess plus récent:
+merge_puzzle_db = Mettre à jour la base de données locale
+puzzles_added = Ajoutés:{" "}
+puzzles_updated = mis à jour:{" "}
+puzzles_removed = Puzzles supprimés :{" "}
+puzzles_missing = qui ne sont plus dans le fichier :{" "}
+remove_missing_puzzles = Supprimer les puzzles qui ne sont plus dans le fichier :{" "}
+merge_parse_error = Le fichier n'a pas pu être lu jusqu'au bout, rien n'a été modifié :{" "}
+merge_too_few = Le fichier contient beaucoup moins de puzzles que la base de données, rien n'a été modifié. Puzzles dans le fichier / dans la base :{" "}
+favs_refreshed = favoris mis à jour:{" "}
+removed_ids_saved = Les ids des puzzles qui ne sont plus dans le fichier ont été enregistrés dans{" "}
 
 #Puzzles
 #============================================================
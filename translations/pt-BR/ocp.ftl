@@ -26,6 +26,7 @@ correct_puzzle = Boa!!
 wrong_move_white_play = Eita, lance errado... Brancas jogam.
 wrong_move_black_play = Eita, lance errado... Pretas jogam.
 all_puzzles_done = Todos os problemas dessa busca já resolvidos!
+your_rating = Seu rating:{" "}
 no_puzzle_found = Desculpe, nenhum problema encontrado.
 
 #Notation
@@ -38,16 +39,106 @@ king = R
 #Search Tab
 lichess_db = Banco de Dados do Lichess
 my_favories = Meus Favoritos
+at_my_level = No meu nível
+level_window = Distância do meu rating:{" "}
 min_rating = Rating Min.:{" "}
 max_rating = Rating Máx.:{" "}
+min_moves = Mín. de lances a jogar:{" "}
+max_moves = Máx. de lances a jogar:{" "}
+min_popularity = Popularidade Min.:{" "}
+max_popularity = Popularidade Máx.:{" "}
+min_rd = Desvio de Rating Min.:{" "}
+max_rd = Desvio de Rating Máx.:{" "}
+times_played_range = Nº de vezes jogado:
+no_limit = Sem limite
 theme_label = Tema Tático:
+more_themes = Combinar com mais temas:
+theme_all_of = Deve ter
+theme_any_of = Algum de
+theme_none_of = Não deve ter
 in_opening = Na Abertura:
 in_the_variation = Variante:
+loading_openings = Lendo as aberturas do arquivo de problemas:{" "}
+opening_line = Ou os lances da abertura ou códigos ECO:
+unknown_opening_line = Nenhuma abertura conhecida corresponde a esses lances ou códigos ECO.
 side = Lado:
 any = Ambos
 white = Brancas
 black = Pretas
+position_pattern = Padrão de posição (opcional):
+position_pattern_help = ex. "RP vs R" (material das brancas vs pretas), "kg8 pf7" (rei preto em g8, peão em f7), "-Qq" (sem damas) ou parte de um FEN como "5ppp/"
+invalid_pattern = Padrão de posição inválido.
 searching = Procurando, aguarde...
+puzzles_read = Puzzles lidos:{" "}
+puzzles_found = encontrados:{" "}
+cancel_search = Cancelar
+search_cancelled = Busca cancelada, puzzles encontrados:{" "}
+load_partial_results = Carregá-los
+puzzle_id_list = Ou carregue uma lista de ids de problemas (ou o caminho de um arquivo com eles):
+load_id_list = Carregar
+review_due = Revisar os problemas errados pendentes
+puzzle_rush = Puzzle rush:{" "}
+rush_time = Tempo:{" "}
+rush_score = Pontos:{" "}
+rush_strikes = Erros:{" "}
+rush_best = Recorde:{" "}
+rush_new_best = Novo recorde pessoal!
+rush_over = Fim da rodada!
+stop_rush = Parar a rodada
+stats = Estatísticas
+stats_attempts = Tentativas
+stats_solved = Resolvidos
+stats_avg_time = Tempo médio
+stats_performance = Performance
+stats_overall = Todos os problemas
+stats_by = Por:{" "}
+stats_by_theme = Tema
+stats_by_opening = Abertura
+stats_by_rating = Rating
+stats_refresh = Atualizar
+stats_last_week = Últimos 7 dias:{" "}
+stats_last_month = Últimos 30 dias:{" "}
+profile = Perfil:
+new_profile = Nome do novo perfil
+create_profile = Criar perfil
+profile_created = Perfil criado.
+profile_name_taken = Já existe um perfil com esse nome.
+board_display = Peças:
+display_normal = Normal
+display_blindfold = Às cegas
+display_fading = Desaparecendo
+display_monochrome = Uma cor
+fade_seconds = Esconder as peças após (segundos):
+position_fen = FEN:{" "}
+moves_played = Lances:{" "}
+type_move = Digite um lance
+play_move = Jogar
+invalid_typed_move = Esse lance não é legal aqui.
+session_review = Revisão da sessão
+puzzle_info = Info do problema
+session_empty = Nenhum problema tentado nesse conjunto ainda.
+outcome_solved = Resolvido
+outcome_hints = Resolvido com dicas
+outcome_failed = Errado
+replay = Jogar de novo
+analyze = Analisar
+start_streak = Sequência
+streak_length = Sequência:{" "}
+no_ids = Nenhum id de problema para carregar.
+unresolved_ids = Problemas não encontrados:{" "}
+seed = Semente aleatória (opcional):
+last_seed = Semente da última busca:{" "}
+puzzle_order = Ordem:{" "}
+order_random = Aleatória
+order_rating_asc = Rating, crescente
+order_rating_desc = Rating, decrescente
+order_popularity = Mais populares primeiro
+order_file = Ordem do arquivo
+saved_searches = Buscas salvas:{" "}
+preset_name = Nome da busca
+save_preset = Salvar
+rename_preset = Renomear
+delete_preset = Excluir
 btn_search = Buscar
 promotion_piece = Peça para promoção:
 
@@ -65,13 +156,31 @@ auto_load = Carregar próx. problema automaticamente:
 flip_board = Girar tabuleiro:
 show_coords = Coordenadas do tabuleiro:
 pdf_number_of_pages = N. de pags. para exportar em PDF:
-get_first_puzzles1 = Obter os primeiros
+get_first_puzzles1 = Obter até
 get_first_puzzles2 =  {" "}problemas
 engine_path = Caminho para a engine (com o .exe):
 save = Salvar Mudanças
 settings_saved = Configurações salvas!
 error_saving = Erro ao salvar configuração.
 error_reading_config = Erro ao ler arquivo de configuração.
+import_puzzle_db = Importar o arquivo de problemas para o banco de dados local
+importing_puzzles = Importando, isso pode levar alguns minutos...
+puzzles_imported = {" "}problemas importados, as buscas usarão o banco de dados local.
+error_importing = Erro ao importar o arquivo de problemas.
+puzzle_source = As buscas usam os problemas importados de:{" "}
+puzzle_source_csv = As buscas leem o arquivo de problemas:{" "}
+puzzle_source_changed = O arquivo de problemas nas configurações mudou desde a importação, importe-o novamente para buscá-lo.
+new_puzzle_file = Arquivo de problemas mais recente do lichess:
+merge_puzzle_db = Atualizar o banco de dados local
+puzzles_added = Adicionados:{" "}
+puzzles_updated = atualizados:{" "}
+puzzles_removed = Problemas removidos:{" "}
+puzzles_missing = que não estão mais no arquivo:{" "}
+remove_missing_puzzles = Remover os problemas que não estão mais no arquivo:{" "}
+merge_parse_error = Não foi possível ler o arquivo até o fim, nada foi alterado:{" "}
+merge_too_few = O arquivo tem muito menos problemas que o banco de dados, nada foi alterado. Problemas no arquivo / no banco de dados:{" "}
+favs_refreshed = favoritos atualizados:{" "}
+removed_ids_saved = Os ids dos problemas que não estão mais no arquivo foram salvos em{" "}
 
 #Puzzles
 #============================================================